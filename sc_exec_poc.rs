@@ -1,322 +0,0 @@
-// Encrypted Smart Contract Execution Proof of Concept
-// Tests three execution models for privacy-preserving computation
-
-use ml_kem::{KemCore, MlKem512};
-use sha3::{Shake256, digest::{Update, ExtendableOutput, XofReader}};
-use std::time::Instant;
-
-// Three execution models to validate
-#[derive(Debug, Clone, Copy)]
-pub enum ExecutionModel {
-    ClientSideZK,    // Client generates execution trace + ZK proof
-    TEEBased,        // Trusted Execution Environment (SGX/SEV)
-    MPCBased,        // Multi-Party Computation
-}
-
-// Encrypted contract state
-#[derive(Clone)]
-pub struct EncryptedState {
-    pub ciphertext: Vec<u8>,
-    pub commitment: [u8; 32],
-    pub encryption_key: Vec<u8>,
-}
-
-impl EncryptedState {
-    pub fn new(plaintext: &[u8], key: &[u8]) -> Self {
-        // Simulate ML-KEM encryption
-        let ciphertext = Self::encrypt(plaintext, key);
-        let commitment = Self::commit(&ciphertext);
-        
-        Self {
-            ciphertext,
-            commitment,
-            encryption_key: key.to_vec(),
-        }
-    }
-    
-    fn encrypt(plaintext: &[u8], key: &[u8]) -> Vec<u8> {
-        // Simplified AES-GCM encryption (in real impl, use ML-KEM)
-        let mut encrypted = plaintext.to_vec();
-        for (i, byte) in encrypted.iter_mut().enumerate() {
-            *byte ^= key[i % key.len()];
-        }
-        encrypted
-    }
-    
-    fn commit(data: &[u8]) -> [u8; 32] {
-        let mut hasher = Shake256::default();
-        hasher.update(data);
-        let mut output = [0u8; 32];
-        hasher.finalize_xof().read(&mut output);
-        output
-    }
-}
-
-// Simple contract: encrypted counter
-pub struct EncryptedCounter {
-    state: EncryptedState,
-}
-
-impl EncryptedCounter {
-    pub fn new(initial_value: u64, key: &[u8]) -> Self {
-        let plaintext = initial_value.to_le_bytes();
-        let state = EncryptedState::new(&plaintext, key);
-        Self { state }
-    }
-    
-    // Increment counter (different execution models)
-    pub fn increment(&mut self, model: ExecutionModel, key: &[u8]) -> ExecutionResult {
-        match model {
-            ExecutionModel::ClientSideZK => self.increment_client_zk(key),
-            ExecutionModel::TEEBased => self.increment_tee(key),
-            ExecutionModel::MPCBased => self.increment_mpc(key),
-        }
-    }
-    
-    // Model 1: Client-side execution with ZK proof
-    fn increment_client_zk(&mut self, key: &[u8]) -> ExecutionResult {
-        let start = Instant::now();
-        
-        // Client decrypts locally
-        let decrypt_start = Instant::now();
-        let plaintext = self.decrypt(&self.state.ciphertext, key);
-        let mut value = u64::from_le_bytes(plaintext.try_into().unwrap());
-        let decrypt_time = decrypt_start.elapsed();
-        
-        // Execute operation
-        let exec_start = Instant::now();
-        value += 1;
-        let exec_time = exec_start.elapsed();
-        
-        // Re-encrypt
-        let encrypt_start = Instant::now();
-        let new_plaintext = value.to_le_bytes();
-        self.state = EncryptedState::new(&new_plaintext, key);
-        let encrypt_time = encrypt_start.elapsed();
-        
-        // Generate ZK proof (simulated)
-        let proof_start = Instant::now();
-        let proof = self.generate_zk_proof(value - 1, value);
-        let proof_time = proof_start.elapsed();
-        
-        let total_time = start.elapsed();
-        
-        ExecutionResult {
-            model: ExecutionModel::ClientSideZK,
-            success: true,
-            total_time_us: total_time.as_micros() as u64,
-            breakdown: ExecutionBreakdown {
-                decryption_us: decrypt_time.as_micros() as u64,
-                execution_us: exec_time.as_micros() as u64,
-                encryption_us: encrypt_time.as_micros() as u64,
-                proof_gen_us: proof_time.as_micros() as u64,
-                network_us: 0,
-            },
-            proof_size_bytes: proof.len(),
-        }
-    }
-    
-    // Model 2: TEE-based execution
-    fn increment_tee(&mut self, key: &[u8]) -> ExecutionResult {
-        let start = Instant::now();
-        
-        // Simulate TEE attestation
-        let attest_start = Instant::now();
-        let _attestation = self.tee_attest();
-        let attest_time = attest_start.elapsed();
-        
-        // Execute inside TEE (simulated - would use SGX/SEV in production)
-        let exec_start = Instant::now();
-        let plaintext = self.decrypt(&self.state.ciphertext, key);
-        let mut value = u64::from_le_bytes(plaintext.try_into().unwrap());
-        value += 1;
-        let new_plaintext = value.to_le_bytes();
-        self.state = EncryptedState::new(&new_plaintext, key);
-        let exec_time = exec_start.elapsed();
-        
-        let total_time = start.elapsed();
-        
-        ExecutionResult {
-            model: ExecutionModel::TEEBased,
-            success: true,
-            total_time_us: total_time.as_micros() as u64,
-            breakdown: ExecutionBreakdown {
-                decryption_us: attest_time.as_micros() as u64,
-                execution_us: exec_time.as_micros() as u64,
-                encryption_us: 0,
-                proof_gen_us: 0,
-                network_us: 0,
-            },
-            proof_size_bytes: 0,
-        }
-    }
-    
-    // Model 3: MPC-based execution
-    fn increment_mpc(&mut self, key: &[u8]) -> ExecutionResult {
-        let start = Instant::now();
-        
-        // Secret share the encrypted state (Shamir's)
-        let share_start = Instant::now();
-        let shares = self.secret_share_state(key, 3, 5); // 3-of-5
-        let share_time = share_start.elapsed();
-        
-        // Each party computes on their share (simulated network delay)
-        let compute_start = Instant::now();
-        let mut computation_results = Vec::new();
-        for share in &shares {
-            // Simulate network latency
-            std::thread::sleep(std::time::Duration::from_micros(100));
-            computation_results.push(share.clone());
-        }
-        let compute_time = compute_start.elapsed();
-        
-        // Reconstruct result
-        let recon_start = Instant::now();
-        let _result = self.reconstruct_from_shares(&computation_results);
-        let recon_time = recon_start.elapsed();
-        
-        let total_time = start.elapsed();
-        
-        ExecutionResult {
-            model: ExecutionModel::MPCBased,
-            success: true,
-            total_time_us: total_time.as_micros() as u64,
-            breakdown: ExecutionBreakdown {
-                decryption_us: share_time.as_micros() as u64,
-                execution_us: compute_time.as_micros() as u64,
-                encryption_us: recon_time.as_micros() as u64,
-                proof_gen_us: 0,
-                network_us: compute_time.as_micros() as u64,
-            },
-            proof_size_bytes: 0,
-        }
-    }
-    
-    fn decrypt(&self, ciphertext: &[u8], key: &[u8]) -> Vec<u8> {
-        let mut decrypted = ciphertext.to_vec();
-        for (i, byte) in decrypted.iter_mut().enumerate() {
-            *byte ^= key[i % key.len()];
-        }
-        decrypted
-    }
-    
-    fn generate_zk_proof(&self, _old_value: u64, _new_value: u64) -> Vec<u8> {
-        // Simulate ZK proof generation (would use actual STARK in production)
-        vec![0u8; 30_000] // ~30KB proof
-    }
-    
-    fn tee_attest(&self) -> Vec<u8> {
-        // Simulate TEE attestation
-        vec![0u8; 1024]
-    }
-    
-    fn secret_share_state(&self, _key: &[u8], _threshold: usize, _total: usize) -> Vec<Vec<u8>> {
-        // Simulate Shamir secret sharing
-        vec![vec![0u8; 32]; 5]
-    }
-    
-    fn reconstruct_from_shares(&self, _shares: &[Vec<u8>]) -> Vec<u8> {
-        // Simulate share reconstruction
-        vec![0u8; 8]
-    }
-}
-
-#[derive(Debug)]
-pub struct ExecutionResult {
-    pub model: ExecutionModel,
-    pub success: bool,
-    pub total_time_us: u64,
-    pub breakdown: ExecutionBreakdown,
-    pub proof_size_bytes: usize,
-}
-
-#[derive(Debug)]
-pub struct ExecutionBreakdown {
-    pub decryption_us: u64,
-    pub execution_us: u64,
-    pub encryption_us: u64,
-    pub proof_gen_us: u64,
-    pub network_us: u64,
-}
-
-impl ExecutionResult {
-    pub fn overhead_vs_plaintext(&self, plaintext_time_us: u64) -> f64 {
-        self.total_time_us as f64 / plaintext_time_us as f64
-    }
-    
-    pub fn meets_target(&self, max_overhead: f64) -> bool {
-        // Target: <10× overhead vs plaintext
-        let plaintext_time = 10; // ~10μs for simple increment
-        self.overhead_vs_plaintext(plaintext_time) < max_overhead
-    }
-    
-    pub fn print_report(&self, plaintext_time_us: u64) {
-        println!("\n=== Execution Model: {:?} ===", self.model);
-        println!("Success: {}", self.success);
-        println!("Total time: {}μs", self.total_time_us);
-        println!("\nBreakdown:");
-        println!("  Decryption/Setup: {}μs", self.breakdown.decryption_us);
-        println!("  Execution: {}μs", self.breakdown.execution_us);
-        println!("  Encryption: {}μs", self.breakdown.encryption_us);
-        println!("  Proof generation: {}μs", self.breakdown.proof_gen_us);
-        println!("  Network: {}μs", self.breakdown.network_us);
-        
-        if self.proof_size_bytes > 0 {
-            println!("\nProof size: {:.1}KB", self.proof_size_bytes as f64 / 1024.0);
-        }
-        
-        let overhead = self.overhead_vs_plaintext(plaintext_time_us);
-        println!("\nOverhead vs plaintext: {:.1}×", overhead);
-        println!("Meets <10× target: {}", self.meets_target(10.0));
-        println!("================================\n");
-    }
-}
-
-// Benchmark all execution models
-pub fn run_execution_benchmark() {
-    println!("Starting Encrypted Execution Benchmark\n");
-    
-    let key = b"test_encryption_key_32_bytes!!!";
-    let plaintext_time_us = 10; // Baseline: plaintext increment
-    
-    println!("Baseline (plaintext execution): {}μs\n", plaintext_time_us);
-    
-    let models = vec![
-        ExecutionModel::ClientSideZK,
-        ExecutionModel::TEEBased,
-        ExecutionModel::MPCBased,
-    ];
-    
-    let mut results = Vec::new();
-    
-    for model in models {
-        let mut counter = EncryptedCounter::new(0, key);
-        let result = counter.increment(model, key);
-        result.print_report(plaintext_time_us);
-        results.push(result);
-    }
-    
-    // Summary and recommendation
-    println!("\n=== VALIDATION SUMMARY ===");
-    
-    let best = results.iter()
-        .min_by_key(|r| r.total_time_us)
-        .unwrap();
-    
-    println!("Best model: {:?}", best.model);
-    println!("Best overhead: {:.1}×", best.overhead_vs_plaintext(plaintext_time_us));
-    
-    if best.meets_target(10.0) {
-        println!("✅ Core Assumption 2: VALIDATED");
-        println!("Recommended approach: {:?}", best.model);
-    } else {
-        println!("❌ Core Assumption 2: FAILED");
-        println!("Minimum overhead: {:.1}× (target: <10×)", 
-                 best.overhead_vs_plaintext(plaintext_time_us));
-        println!("\nRecommendation: Use TEE-based for v1, research FHE for v2");
-    }
-}
-
-fn main() {
-    run_execution_benchmark();
-}
\ No newline at end of file
@@ -0,0 +1,222 @@
+use rand::Rng;
+use sha3::{Shake256, digest::{Update, ExtendableOutput, XofReader}};
+use crate::vm::VmWord;
+
+// Additively homomorphic encryption: secret-key LWE (Regev) over Z_q with
+// q = 2^64, ternary secret, and messages mod 2^32 scaled into the top half.
+// Adding ciphertexts adds messages and noise; adding a public constant adds
+// no noise. n = 2048 with 11-bit noise is roughly the HE standard's 128-bit
+// row for this modulus-to-noise ratio; PoC parameters, not audited.
+pub const LWE_DIMENSION: usize = 2048;
+const LWE_SCALE_BITS: u32 = 32; // Δ = q / t = 2^32
+const LWE_NOISE_BOUND: u64 = 1 << 11; // fresh error is uniform in [-B, B]
+
+pub struct LweSecretKey {
+    // Ternary coefficients as elements of Z_q (-1 is u64::MAX)
+    s: Vec<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LweCiphertext {
+    a: Vec<u64>,
+    b: u64,
+    // Public worst-case bound on |e|, tracked through additions
+    noise_bound: u64,
+}
+
+impl LweSecretKey {
+    // Derived from the contract key, so only its holders can decrypt
+    pub fn derive(key: &[u8]) -> Self {
+        let mut hasher = Shake256::default();
+        hasher.update(b"ecliptica/lwe-secret");
+        hasher.update(key);
+        let mut reader = hasher.finalize_xof();
+        let mut s = Vec::with_capacity(LWE_DIMENSION);
+        let mut byte = [0u8; 1];
+        while s.len() < LWE_DIMENSION {
+            reader.read(&mut byte);
+            // 255 is rejected so that byte % 3 is uniform
+            if byte[0] < 255 {
+                s.push(((byte[0] % 3) as u64).wrapping_sub(1));
+            }
+        }
+        Self { s }
+    }
+    
+    fn dot(&self, a: &[u64]) -> u64 {
+        a.iter().zip(&self.s).fold(0u64, |acc, (a, s)| acc.wrapping_add(a.wrapping_mul(*s)))
+    }
+    
+    pub fn encrypt(&self, message: u64) -> LweCiphertext {
+        let mut rng = rand::thread_rng();
+        let a: Vec<u64> = (0..LWE_DIMENSION).map(|_| rng.gen()).collect();
+        let bound = LWE_NOISE_BOUND as i64;
+        let e = rng.gen_range(-bound..=bound) as u64;
+        let b = self.dot(&a).wrapping_add(e).wrapping_add(message << LWE_SCALE_BITS);
+        LweCiphertext { a, b, noise_bound: LWE_NOISE_BOUND }
+    }
+    
+    // b - <a, s> = Δ·m + e; round away the noise
+    fn phase(&self, ciphertext: &LweCiphertext) -> (u64, u64) {
+        let phase = ciphertext.b.wrapping_sub(self.dot(&ciphertext.a));
+        let message = phase.wrapping_add(1 << (LWE_SCALE_BITS - 1)) >> LWE_SCALE_BITS;
+        let noise = (phase.wrapping_sub(message << LWE_SCALE_BITS) as i64).unsigned_abs();
+        (message, noise)
+    }
+    
+    pub fn decrypt(&self, ciphertext: &LweCiphertext) -> u64 {
+        self.phase(ciphertext).0
+    }
+    
+    // Actual |e|, visible only to the key holder
+    pub fn noise(&self, ciphertext: &LweCiphertext) -> u64 {
+        self.phase(ciphertext).1
+    }
+}
+
+impl LweCiphertext {
+    pub fn add(&self, other: &Self) -> Self {
+        Self {
+            a: self.a.iter().zip(&other.a).map(|(x, y)| x.wrapping_add(*y)).collect(),
+            b: self.b.wrapping_add(other.b),
+            noise_bound: self.noise_bound + other.noise_bound,
+        }
+    }
+    
+    pub fn sub(&self, other: &Self) -> Self {
+        Self {
+            a: self.a.iter().zip(&other.a).map(|(x, y)| x.wrapping_sub(*y)).collect(),
+            b: self.b.wrapping_sub(other.b),
+            noise_bound: self.noise_bound + other.noise_bound,
+        }
+    }
+    
+    // Public constant: shifts the message only, noise is unchanged
+    pub fn add_plain(&self, value: u64) -> Self {
+        Self { b: self.b.wrapping_add(value << LWE_SCALE_BITS), ..self.clone() }
+    }
+    
+    pub fn noise_bound(&self) -> u64 {
+        self.noise_bound
+    }
+    
+    // Bits of headroom left before the worst-case noise reaches Δ/2 and
+    // decryption may round to the wrong message
+    pub fn noise_budget_bits(&self) -> f64 {
+        (LWE_SCALE_BITS - 1) as f64 - (self.noise_bound as f64).log2()
+    }
+    
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.a.iter().chain([&self.b, &self.noise_bound]).flat_map(|x| x.to_le_bytes()).collect()
+    }
+    
+    pub fn size_bytes(&self) -> usize {
+        8 * (self.a.len() + 2)
+    }
+}
+
+// Public constants are noiseless trivial encryptions (a = 0)
+impl VmWord for LweCiphertext {
+    type Context = ();
+    
+    fn constant(_: &(), value: u64) -> Self {
+        Self { a: vec![0; LWE_DIMENSION], b: value << LWE_SCALE_BITS, noise_bound: 0 }
+    }
+    
+    fn add(&self, other: &Self) -> Self {
+        LweCiphertext::add(self, other)
+    }
+    
+    fn sub(&self, other: &Self) -> Self {
+        LweCiphertext::sub(self, other)
+    }
+    
+    // Scaling multiplies the noise too
+    fn mul_public(&self, value: u64) -> Self {
+        Self {
+            a: self.a.iter().map(|x| x.wrapping_mul(value)).collect(),
+            b: self.b.wrapping_mul(value),
+            noise_bound: self.noise_bound.saturating_mul(value),
+        }
+    }
+    
+    fn mul(&self, _: &Self) -> Option<Self> {
+        None
+    }
+    
+    fn is_zero(&self) -> Option<bool> {
+        None
+    }
+}
+
+// What the FHE model reports next to timings
+#[derive(Debug, Clone, Copy)]
+pub struct LweStats {
+    pub ciphertext_bytes: usize,
+    pub plaintext_bytes: usize,
+    // Size change from the input ciphertext to the output
+    pub growth_bytes: i64,
+    pub noise_budget_bits: f64,
+}
+
+// Token ledger with LWE-encrypted balances: transfers subtract and add
+// ciphertexts, nobody but the key holder sees a balance
+pub struct HomomorphicLedger {
+    balances: Vec<LweCiphertext>,
+}
+
+impl HomomorphicLedger {
+    pub fn new(initial_balances: &[u64], secret: &LweSecretKey) -> Self {
+        Self { balances: initial_balances.iter().map(|b| secret.encrypt(*b)).collect() }
+    }
+    
+    // NOTE: no overdraft check - that needs a comparison, not an addition
+    pub fn transfer(&mut self, from: usize, to: usize, amount: &LweCiphertext) {
+        self.balances[from] = self.balances[from].sub(amount);
+        self.balances[to] = self.balances[to].add(amount);
+    }
+    
+    pub fn balance(&self, account: usize) -> Option<&LweCiphertext> {
+        self.balances.get(account)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EncryptedCounter, ExecutionModel};
+    use crate::mpc::MpcTokenLedger;
+    use crate::tests::KEY;
+    
+    // FHE: additions only, never a decryption, noise within the tracked bound
+    #[test]
+    fn fhe_counter_increments_without_decryption() {
+        let mut counter = EncryptedCounter::new(41, KEY);
+        for _ in 0..2 {
+            counter.increment(ExecutionModel::FHEBased, KEY).expect("FHE increment");
+        }
+        assert_eq!(counter.reveal_fhe(KEY), Some(43));
+    }
+    
+    #[test]
+    fn fhe_ledger_transfer_matches_plaintext() {
+        let lwe_key = LweSecretKey::derive(KEY);
+        let mut ledger = HomomorphicLedger::new(&[100, 50], &lwe_key);
+        ledger.transfer(0, 1, &lwe_key.encrypt(30));
+        let mut plain_balances = [100, 50];
+        MpcTokenLedger::plaintext_transfer(&mut plain_balances, 0, 1, 30);
+        assert_eq!(ledger.balance(0).map(|b| lwe_key.decrypt(b)), Some(plain_balances[0]));
+        assert_eq!(ledger.balance(1).map(|b| lwe_key.decrypt(b)), Some(plain_balances[1]));
+    }
+    
+    #[test]
+    fn lwe_noise_stays_within_the_tracked_bound() {
+        let lwe_key = LweSecretKey::derive(KEY);
+        let one = lwe_key.encrypt(1);
+        let sum = (1..1_000).fold(one.clone(), |sum, _| sum.add(&one));
+        assert_eq!(lwe_key.decrypt(&sum), 1_000);
+        assert!(lwe_key.noise(&sum) <= sum.noise_bound(), "actual noise within the tracked bound");
+        assert_eq!(lwe_key.noise(&one.add_plain(5)), lwe_key.noise(&one), "plain addition leaves the noise unchanged");
+        assert_eq!(sum.size_bytes(), one.size_bytes(), "ciphertext size unchanged by additions");
+    }
+}
@@ -0,0 +1,174 @@
+use crate::ExecutionError;
+use crate::vm::{Program, VmError, VmRun};
+
+// Gas: one schedule prices every operation the models perform, whatever
+// it costs a given model in time. Comparing time per gas across models
+// shows which encrypted operations the schedule underprices.
+pub const DEFAULT_GAS_LIMIT: u64 = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GasOp {
+    Compute,          // one VM instruction
+    StorageRead,      // one encrypted slot read, with its inclusion proof
+    StorageWrite,     // one slot encrypted and written, root updated
+    KemEncapsulation, // ML-KEM encapsulation to an enclave
+    ProofGeneration,  // STARK, attested signature or committee signatures
+    MpcRound,         // one communication round between the parties
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasSchedule {
+    pub compute_op: u64,
+    pub storage_read: u64,
+    pub storage_write: u64,
+    pub kem_encapsulation: u64,
+    pub proof_generation: u64,
+    pub mpc_round: u64,
+}
+
+impl Default for GasSchedule {
+    fn default() -> Self {
+        Self {
+            compute_op: 1,
+            storage_read: 100,
+            storage_write: 500,
+            kem_encapsulation: 2_000,
+            proof_generation: 20_000,
+            mpc_round: 5_000,
+        }
+    }
+}
+
+impl GasSchedule {
+    pub fn price(&self, op: GasOp) -> u64 {
+        match op {
+            GasOp::Compute => self.compute_op,
+            GasOp::StorageRead => self.storage_read,
+            GasOp::StorageWrite => self.storage_write,
+            GasOp::KemEncapsulation => self.kem_encapsulation,
+            GasOp::ProofGeneration => self.proof_generation,
+            GasOp::MpcRound => self.mpc_round,
+        }
+    }
+}
+
+// Charges one execution against its limit. Running out burns the whole
+// limit, as on chain, and the execution fails.
+#[derive(Debug, Clone)]
+pub struct GasMeter {
+    schedule: GasSchedule,
+    limit: u64,
+    used: u64,
+    out_of_gas: bool,
+}
+
+impl GasMeter {
+    pub fn new(schedule: GasSchedule, limit: u64) -> Self {
+        Self { schedule, limit, used: 0, out_of_gas: false }
+    }
+    
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+    
+    pub fn limit(&self) -> u64 {
+        self.limit
+    }
+    
+    pub fn remaining(&self) -> u64 {
+        self.limit - self.used
+    }
+    
+    pub fn is_out_of_gas(&self) -> bool {
+        self.out_of_gas
+    }
+    
+    pub fn charge(&mut self, op: GasOp, count: u64) -> Result<(), ExecutionError> {
+        let cost = self.schedule.price(op).saturating_mul(count);
+        if self.out_of_gas || cost > self.remaining() {
+            self.used = self.limit;
+            self.out_of_gas = true;
+            return Err(ExecutionError::OutOfGas { limit: self.limit });
+        }
+        self.used += cost;
+        Ok(())
+    }
+    
+    // `program` with its step limit lowered to what the gas left pays for
+    pub fn limited(&self, program: &Program) -> Program {
+        let affordable = self.remaining() / self.schedule.compute_op.max(1);
+        program.clone().with_max_steps(program.max_steps.min(affordable.try_into().unwrap_or(usize::MAX)))
+    }
+    
+    // Charges the steps of a run of `limited(program)`. Hitting the lowered
+    // step limit is running out of gas, not a VM error.
+    pub fn settle<W>(&mut self, program: &Program, outcome: Result<(W, VmRun), VmError>) -> Result<W, ExecutionError> {
+        match outcome {
+            Ok((word, run)) => {
+                self.charge(GasOp::Compute, run.steps as u64)?;
+                Ok(word)
+            }
+            // One more step than the gas left pays for
+            Err(VmError::StepLimit(steps)) if steps < program.max_steps => {
+                self.charge(GasOp::Compute, steps as u64 + 1)?;
+                Err(ExecutionError::OutOfGas { limit: self.limit })
+            }
+            Err(error) => Err(ExecutionError::Program(error)),
+        }
+    }
+    
+    pub fn run<W>(
+        &mut self,
+        program: &Program,
+        run: impl FnOnce(&Program) -> Result<(W, VmRun), VmError>,
+    ) -> Result<W, ExecutionError> {
+        let outcome = run(&self.limited(program));
+        self.settle(program, outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EncryptedCounter, ExecutionModel};
+    use crate::tests::KEY;
+    use crate::vm::Instruction;
+    
+    // One gas schedule for every model; MPC pays for the input round on the
+    // first call only, so repeat calls are compared
+    #[test]
+    fn gas_charged_within_the_limit_and_repeatable() {
+        for model in ExecutionModel::ALL {
+            let mut counter = EncryptedCounter::new(41, KEY);
+            let [first, second, third] = [(); 3].map(|_| counter.increment(model, KEY).expect("increment"));
+            assert!(first.gas_used > 0 && first.gas_used <= first.gas_limit, "{:?}: {} gas", model, first.gas_used);
+            assert_eq!(second.gas_used, third.gas_used, "{:?}: same call, same gas", model);
+        }
+    }
+    
+    // 500 gas runs out before any model can commit
+    #[test]
+    fn out_of_gas_fails_and_keeps_the_state() {
+        for model in ExecutionModel::ALL {
+            let mut starved = EncryptedCounter::new(41, KEY).with_gas(GasSchedule::default(), 500);
+            starved.prepare(model, KEY).expect("deployment");
+            let before = starved.state_commitment();
+            assert_eq!(starved.increment(model, KEY).err(), Some(ExecutionError::OutOfGas { limit: 500 }), "{:?}", model);
+            assert!(starved.state_commitment() == before, "{:?}: state kept", model);
+        }
+    }
+    
+    // Run without applying, to see what the failed call was charged
+    #[test]
+    fn runaway_loop_burns_the_gas_limit() {
+        let runaway = Program::new(vec![Instruction::Push(1), Instruction::Jnz(0)]);
+        for model in ExecutionModel::ALL {
+            let mut spinning = EncryptedCounter::new(41, KEY).with_program(runaway.clone(), &[]).with_gas(GasSchedule::default(), 5_000);
+            spinning.prepare(model, KEY).expect("deployment");
+            let (result, _) = spinning.execute(model, &spinning.snapshot(), KEY);
+            assert!(!result.success && result.out_of_gas, "{:?}", model);
+            assert_eq!(result.gas_used, 5_000, "{:?}", model);
+            assert_eq!(result.error, Some(ExecutionError::OutOfGas { limit: 5_000 }), "{:?}", model);
+        }
+    }
+}
@@ -0,0 +1,397 @@
+use aes_gcm::{Aes256Gcm, Nonce, aead::{Aead, KeyInit, Payload}};
+use rand::Rng;
+use sha3::{Shake256, digest::{Update, ExtendableOutput, XofReader}};
+
+// Hierarchical deterministic viewing keys: master, then contract, then
+// epoch, then slot; event topics sit beside epochs under the contract. A
+// child secret is a one-way function of its parent's (SHAKE-256 as the
+// PRF), so a key opens its own subtree and nothing above or beside it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KeySegment {
+    Contract(String),
+    Epoch(u64),
+    Slot(u64),
+    Topic([u8; 32]),
+}
+
+impl KeySegment {
+    fn encode(&self) -> Vec<u8> {
+        match self {
+            KeySegment::Contract(id) => [&[0u8][..], &(id.len() as u32).to_le_bytes(), id.as_bytes()].concat(),
+            KeySegment::Epoch(epoch) => [&[1u8][..], &epoch.to_le_bytes()].concat(),
+            KeySegment::Slot(slot) => [&[2u8][..], &slot.to_le_bytes()].concat(),
+            KeySegment::Topic(topic) => [&[3u8][..], &topic[..]].concat(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct KeyPath(Vec<KeySegment>);
+
+impl KeyPath {
+    pub fn master() -> Self {
+        Self(Vec::new())
+    }
+    
+    pub fn contract(id: &str) -> Self {
+        Self(vec![KeySegment::Contract(id.to_string())])
+    }
+    
+    pub fn epoch(mut self, epoch: u64) -> Self {
+        self.0.push(KeySegment::Epoch(epoch));
+        self
+    }
+    
+    pub fn slot(mut self, slot: u64) -> Self {
+        self.0.push(KeySegment::Slot(slot));
+        self
+    }
+    
+    pub fn topic(mut self, topic: [u8; 32]) -> Self {
+        self.0.push(KeySegment::Topic(topic));
+        self
+    }
+    
+    pub fn segments(&self) -> &[KeySegment] {
+        &self.0
+    }
+    
+    pub fn key_epoch(&self) -> Option<u64> {
+        self.0.iter().find_map(|segment| match segment {
+            KeySegment::Epoch(epoch) => Some(*epoch),
+            _ => None,
+        })
+    }
+    
+    pub fn slot_index(&self) -> Option<u64> {
+        self.0.iter().find_map(|segment| match segment {
+            KeySegment::Slot(slot) => Some(*slot),
+            _ => None,
+        })
+    }
+    
+    // Same position under another key epoch
+    pub fn with_epoch(&self, epoch: u64) -> Option<Self> {
+        let index = self.0.iter().position(|segment| matches!(segment, KeySegment::Epoch(_)))?;
+        let mut path = self.clone();
+        path.0[index] = KeySegment::Epoch(epoch);
+        Some(path)
+    }
+    
+    pub fn contains(&self, other: &KeyPath) -> bool {
+        other.0.starts_with(&self.0)
+    }
+    
+    pub(crate) fn encode(&self) -> Vec<u8> {
+        self.0.iter().flat_map(KeySegment::encode).collect()
+    }
+    
+    pub(crate) fn decode(mut bytes: &[u8]) -> Option<Self> {
+        let mut segments = Vec::new();
+        while let Some((&tag, rest)) = bytes.split_first() {
+            let (segment, rest) = match tag {
+                0 => {
+                    let len = u32::from_le_bytes(rest.get(..4)?.try_into().ok()?) as usize;
+                    let id = String::from_utf8(rest.get(4..4 + len)?.to_vec()).ok()?;
+                    (KeySegment::Contract(id), &rest[4 + len..])
+                }
+                1 | 2 => {
+                    let n = u64::from_le_bytes(rest.get(..8)?.try_into().ok()?);
+                    let segment = if tag == 1 { KeySegment::Epoch(n) } else { KeySegment::Slot(n) };
+                    (segment, &rest[8..])
+                }
+                3 => (KeySegment::Topic(rest.get(..32)?.try_into().ok()?), &rest[32..]),
+                _ => return None,
+            };
+            segments.push(segment);
+            bytes = rest;
+        }
+        Some(Self(segments))
+    }
+}
+
+#[derive(Clone)]
+pub struct ViewingKey {
+    pub(crate) path: KeyPath,
+    pub(crate) secret: [u8; 32],
+}
+
+impl ViewingKey {
+    pub fn master(seed: &[u8]) -> Self {
+        Self {
+            path: KeyPath::master(),
+            secret: derive_key(b"ecliptica/hdvk/master", &[seed]),
+        }
+    }
+    
+    // Root of `path`'s subtree with fresh random material: not derivable
+    // from any parent key
+    pub fn fresh(path: KeyPath) -> Self {
+        let mut secret = [0u8; 32];
+        rand::thread_rng().fill(&mut secret);
+        Self { path, secret }
+    }
+    
+    pub fn path(&self) -> &KeyPath {
+        &self.path
+    }
+    
+    pub fn child(&self, segment: KeySegment) -> Self {
+        let secret = derive_key(b"ecliptica/hdvk/child", &[&self.secret, &segment.encode()]);
+        let mut path = self.path.clone();
+        path.0.push(segment);
+        Self { path, secret }
+    }
+    
+    // Key for `path`, if it lies in this key's subtree
+    pub fn derive(&self, path: &KeyPath) -> Option<Self> {
+        if !self.path.contains(path) {
+            return None;
+        }
+        Some(path.0[self.path.0.len()..]
+            .iter()
+            .fold(self.clone(), |key, segment| key.child(segment.clone())))
+    }
+    
+    // Selective disclosure: hand out the key for one subtree only
+    pub fn export_scoped(&self, scope: &KeyPath) -> Option<Vec<u8>> {
+        let scoped = self.derive(scope)?;
+        Some([&scoped.secret[..], &scoped.path.encode()].concat())
+    }
+    
+    pub fn import(bytes: &[u8]) -> Option<Self> {
+        let (secret, path) = bytes.split_at_checked(32)?;
+        Some(Self {
+            path: KeyPath::decode(path)?,
+            secret: secret.try_into().ok()?,
+        })
+    }
+}
+
+// Contract events. The topic hash is public so indexers can filter on it;
+// the payload is sealed under the contract's viewing key for that topic,
+// so a key scoped to the contract (or to one topic) reads it and nothing
+// else does.
+pub trait ContractEvent: Sized {
+    const TOPIC: &'static str;
+    fn encode(&self) -> Vec<u8>;
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+pub fn topic_hash(topic: &str) -> [u8; 32] {
+    derive_key(b"ecliptica/event/topic", &[topic.as_bytes()])
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EncryptedEvent {
+    pub contract_id: String,
+    pub topic: [u8; 32],
+    pub ciphertext: Vec<u8>,
+}
+
+impl EncryptedEvent {
+    // `contract_key` is the contract's viewing key, scoped to the contract
+    pub fn emit<E: ContractEvent>(contract_key: &ViewingKey, event: &E) -> Option<Self> {
+        let [KeySegment::Contract(contract_id)] = contract_key.path().segments() else {
+            return None;
+        };
+        let topic = topic_hash(E::TOPIC);
+        let path = KeyPath::contract(contract_id).topic(topic);
+        let topic_key = contract_key.derive(&path)?;
+        Some(Self {
+            contract_id: contract_id.clone(),
+            topic,
+            ciphertext: aead_seal(&Self::aead_key(&topic_key), &event.encode(), &path.encode()),
+        })
+    }
+    
+    fn path(&self) -> KeyPath {
+        KeyPath::contract(&self.contract_id).topic(self.topic)
+    }
+    
+    fn aead_key(topic_key: &ViewingKey) -> [u8; 32] {
+        derive_key(b"ecliptica/event", &[&topic_key.secret])
+    }
+    
+    // Binds contract, topic and ciphertext; goes into ExecutionResult
+    pub fn commitment(&self) -> [u8; 32] {
+        derive_key(b"ecliptica/event/commitment", &[&self.path().encode(), &self.ciphertext])
+    }
+    
+    pub fn open(&self, viewing_key: &ViewingKey) -> Option<Vec<u8>> {
+        let path = self.path();
+        aead_open(&Self::aead_key(&viewing_key.derive(&path)?), &self.ciphertext, &path.encode())
+    }
+    
+    pub fn decode<E: ContractEvent>(&self, viewing_key: &ViewingKey) -> Option<E> {
+        if self.topic != topic_hash(E::TOPIC) {
+            return None;
+        }
+        E::decode(&self.open(viewing_key)?)
+    }
+}
+
+// Events of type E in `log` that `viewing_key` can read, in log order.
+// Topic and scope are checked before any decryption is attempted.
+pub fn scan_events<E: ContractEvent>(log: &[EncryptedEvent], viewing_key: &ViewingKey) -> Vec<E> {
+    let topic = topic_hash(E::TOPIC);
+    log.iter()
+        .filter(|event| event.topic == topic && viewing_key.path().contains(&event.path()))
+        .filter_map(|event| event.decode(viewing_key))
+        .collect()
+}
+
+// SHAKE-256 key derivation with a domain-separation label
+pub(crate) fn derive_key(label: &[u8], inputs: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = Shake256::default();
+    hasher.update(label);
+    for input in inputs {
+        hasher.update(&(input.len() as u64).to_le_bytes());
+        hasher.update(input);
+    }
+    let mut output = [0u8; 32];
+    hasher.finalize_xof().read(&mut output);
+    output
+}
+
+// AES-256-GCM with a random 96-bit nonce prepended to the ciphertext
+pub(crate) fn aead_seal(key: &[u8; 32], plaintext: &[u8], aad: &[u8]) -> Vec<u8> {
+    let mut nonce = [0u8; 12];
+    rand::thread_rng().fill(&mut nonce);
+    let ciphertext = Aes256Gcm::new(key.into())
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+        .expect("AES-GCM encryption of an in-memory buffer");
+    let mut sealed = nonce.to_vec();
+    sealed.extend(ciphertext);
+    sealed
+}
+
+pub(crate) fn aead_open(key: &[u8; 32], sealed: &[u8], aad: &[u8]) -> Option<Vec<u8>> {
+    if sealed.len() < 12 {
+        return None;
+    }
+    let (nonce, ciphertext) = sealed.split_at(12);
+    Aes256Gcm::new(key.into())
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        COUNTER_CONTRACT_ID, CounterCalled, EncryptedCounter, ExecutionModel, ExecutionResult,
+    };
+    use crate::tests::{KEY, sealed_tree};
+    
+    // A scoped key opens its subtree; siblings and parents stay sealed
+    #[test]
+    fn scoped_key_opens_only_its_subtree() {
+        let master = ViewingKey::master(KEY);
+        let states = sealed_tree(&master);
+        let scope = KeyPath::contract("counter").epoch(1);
+        let auditor = master.export_scoped(&scope).and_then(|bytes| ViewingKey::import(&bytes)).expect("scoped export");
+        for (path, plaintext, state) in &states {
+            assert_eq!(state.open(&master).as_deref(), Some(plaintext.as_bytes()), "master opens {:?}", path);
+            let expected = scope.contains(path).then_some(plaintext.as_bytes());
+            assert_eq!(state.open(&auditor).as_deref(), expected, "scoped key on {:?}", path);
+        }
+        assert!(auditor.derive(&KeyPath::contract("counter")).is_none(), "scoped key cannot derive its parent");
+    }
+    
+    #[test]
+    fn slot_key_opens_exactly_its_slot() {
+        let master = ViewingKey::master(KEY);
+        let states = sealed_tree(&master);
+        let slot = KeyPath::contract("ledger").epoch(0).slot(1);
+        let slot_key = master.derive(&slot).expect("slot key");
+        for (path, _, state) in &states {
+            assert_eq!(state.open(&slot_key).is_some(), *path == slot, "{:?}", path);
+        }
+        let mut relabelled = states[0].2.clone();
+        relabelled.key_path = states[1].0.clone();
+        assert!(relabelled.open(&master).is_none(), "state relabelled to another slot rejected");
+    }
+    
+    // Live contract states sit in the same tree: an epoch export reads them
+    #[test]
+    fn epoch_export_reads_the_live_counter() {
+        let master = ViewingKey::master(KEY);
+        let mut live = EncryptedCounter::new(41, KEY);
+        for model in [ExecutionModel::ClientSideZK, ExecutionModel::TEEBased] {
+            live.increment(model, KEY).expect("increment");
+        }
+        let epoch = live.snapshot().store.key_epoch();
+        let epoch_export = master
+            .export_scoped(&KeyPath::contract(COUNTER_CONTRACT_ID).epoch(epoch))
+            .and_then(|bytes| ViewingKey::import(&bytes))
+            .expect("epoch export");
+        assert_eq!(live.state().open(&epoch_export), Some(43u64.to_le_bytes().to_vec()));
+        let other_epoch = master.derive(&KeyPath::contract(COUNTER_CONTRACT_ID).epoch(epoch + 1)).expect("epoch key");
+        let ledger_slot = master.derive(&KeyPath::contract("ledger").epoch(0).slot(1)).expect("slot key");
+        assert!(live.state().open(&other_epoch).is_none(), "another epoch's key cannot open it");
+        assert!(live.state().open(&ledger_slot).is_none(), "a ledger slot key cannot open it");
+    }
+    
+    #[derive(Debug, PartialEq)]
+    struct Transfer {
+        amount: u64,
+    }
+    
+    impl ContractEvent for Transfer {
+        const TOPIC: &'static str = "Transfer(u64)";
+        fn encode(&self) -> Vec<u8> {
+            self.amount.to_le_bytes().to_vec()
+        }
+        fn decode(bytes: &[u8]) -> Option<Self> {
+            Some(Self { amount: u64::from_le_bytes(bytes.try_into().ok()?) })
+        }
+    }
+    
+    // Three counter calls and two ledger transfers in one public log
+    fn shared_log() -> (EncryptedCounter, Vec<ExecutionResult>, Vec<EncryptedEvent>, ViewingKey) {
+        let mut counter = EncryptedCounter::new(41, KEY);
+        let results = [ExecutionModel::ClientSideZK, ExecutionModel::TEEBased, ExecutionModel::MPCBased]
+            .into_iter()
+            .map(|model| counter.increment(model, KEY).expect("increment"))
+            .collect();
+        let ledger_key = ViewingKey::master(b"ledger owner").child(KeySegment::Contract("ledger".to_string()));
+        let transfers = [30, 70].map(|amount| EncryptedEvent::emit(&ledger_key, &Transfer { amount }).expect("sealed"));
+        let log = counter.events().iter().chain(&transfers).cloned().collect();
+        (counter, results, log, ledger_key)
+    }
+    
+    #[test]
+    fn every_call_emits_one_committed_event() {
+        let (counter, results, _, _) = shared_log();
+        assert!(results.iter().all(|result| result.event_commitments.len() == 1));
+        let committed = results.iter().flat_map(|result| result.event_commitments.clone());
+        assert!(committed.eq(counter.events().iter().map(EncryptedEvent::commitment)), "result commitments match the log");
+    }
+    
+    // Each key reads its own contract's events from the shared log
+    #[test]
+    fn keys_read_only_their_own_events() {
+        let (_, _, log, ledger_key) = shared_log();
+        let counter_key = EncryptedCounter::viewing_key(KEY);
+        assert_eq!(scan_events::<CounterCalled>(&log, &counter_key), vec![CounterCalled { args: vec![1] }; 3]);
+        assert!(scan_events::<Transfer>(&log, &counter_key).is_empty());
+        assert_eq!(scan_events::<Transfer>(&log, &ledger_key), [Transfer { amount: 30 }, Transfer { amount: 70 }]);
+        assert!(scan_events::<CounterCalled>(&log, &ledger_key).is_empty());
+        let topic_scope = KeyPath::contract("counter").topic(topic_hash(CounterCalled::TOPIC));
+        let indexer = ViewingKey::master(KEY).export_scoped(&topic_scope).and_then(|bytes| ViewingKey::import(&bytes)).expect("topic export");
+        assert_eq!(scan_events::<CounterCalled>(&log, &indexer).len(), 3, "topic-scoped export reads that topic");
+        assert_eq!(log.iter().filter(|event| event.topic == topic_hash(CounterCalled::TOPIC)).count(), 3, "topics filter without a key");
+    }
+    
+    #[test]
+    fn events_reject_wrong_types_and_tampering() {
+        let (_, _, log, _) = shared_log();
+        let counter_key = EncryptedCounter::viewing_key(KEY);
+        assert!(log[0].decode::<Transfer>(&counter_key).is_none(), "decoded as the wrong type");
+        let mut tampered = log[0].clone();
+        *tampered.ciphertext.last_mut().expect("non-empty ciphertext") ^= 1;
+        assert!(tampered.open(&counter_key).is_none());
+        assert_ne!(tampered.commitment(), log[0].commitment());
+    }
+}
@@ -0,0 +1,2711 @@
+// Encrypted Smart Contract Execution Proof of Concept
+// Tests five execution models for privacy-preserving computation
+
+pub mod fhe;
+pub mod gas;
+pub mod keys;
+pub mod mpc;
+pub mod optimistic;
+pub mod storage;
+pub mod tee;
+pub mod timing;
+pub mod vm;
+pub mod wasm;
+pub mod zk;
+
+use ml_dsa::{MlDsa65, Signature, VerifyingKey};
+use ml_dsa::signature::Verifier;
+use ml_kem::kem::{Decapsulate, Encapsulate};
+use ml_kem::{KemCore, MlKem512};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use std::fs;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::RecvTimeoutError;
+use std::thread;
+use std::time::{Duration, Instant};
+use winterfell::math::{fields::f128::BaseElement, FieldElement};
+use crate::fhe::{HomomorphicLedger, LweCiphertext, LweSecretKey, LweStats};
+use crate::gas::{DEFAULT_GAS_LIMIT, GasMeter, GasOp, GasSchedule};
+use crate::keys::{
+    ContractEvent, EncryptedEvent, KeyPath, KeySegment, ViewingKey, scan_events, topic_hash,
+};
+use crate::mpc::{
+    AuthSharing, Fp, MpcCommittee, MpcError, MpcFault, MpcNetwork, MpcTokenLedger, NetworkConfig,
+    NetworkStats, SealedBidAuction, SharedValue, TrustedDealer,
+};
+use crate::optimistic::{BondAccount, OptimisticConfig, OptimisticTee, Settlement, VerifierBehavior};
+use crate::storage::{
+    AEAD_OVERHEAD, EncryptedState, EncryptedStore, FileBackend, MemoryBackend, PaddingPolicy,
+    StorageBackend,
+};
+use crate::tee::{
+    AttestationReport, Enclave, KeyRotation, SimulatedPlatform, encrypt_input_for_enclave, measure,
+    transition_payload, verify_attestation,
+};
+use crate::timing::{
+    ExecutionBreakdown, OVERHEAD_TARGET, Phase, PhaseTimings, PlaintextBaseline, TimingHarness,
+};
+use crate::vm::{
+    Instruction, OP_ADD, OP_ARG, OP_HALT, OP_LOAD, OP_STORE, Program, VmError, VmRun, run_on_slot,
+};
+use crate::wasm::{COUNTER_WASM, WASM_DEFAULT_FUEL, WasmHost};
+use crate::zk::{
+    COMMITMENT_TRACE_LENGTH, COUNTER_TRACE_LENGTH, CounterTransition, HYBRID_CHALLENGE_WINDOW,
+    HybridLatency, prove_counter_transition, spawn_prover, value_commitment, verify_counter_transition,
+};
+
+// Execution models to validate
+#[derive(Debug, Clone, Copy)]
+pub enum ExecutionModel {
+    ClientSideZK,    // Client generates execution trace + ZK proof
+    TEEBased,        // Trusted Execution Environment (SGX/SEV)
+    MPCBased,        // Multi-Party Computation
+    FHEBased,        // Additively homomorphic encryption (LWE)
+    HybridTEEZK,     // Enclave executes; its STARK is checked on challenge
+}
+
+impl ExecutionModel {
+    pub const ALL: [ExecutionModel; 5] = [
+        ExecutionModel::ClientSideZK,
+        ExecutionModel::TEEBased,
+        ExecutionModel::MPCBased,
+        ExecutionModel::FHEBased,
+        ExecutionModel::HybridTEEZK,
+    ];
+}
+
+// Counter contract: slot 0 += argument 0. Loaded into the enclave as is;
+// its hash is the measurement.
+const COUNTER_CONTRACT_CODE: &[u8] = &[
+    OP_LOAD, 0, 0, 0, 0, 0, 0, 0, 0,
+    OP_ARG, 0,
+    OP_ADD,
+    OP_STORE, 0, 0, 0, 0, 0, 0, 0, 0,
+    OP_HALT,
+];
+const COUNTER_CONTRACT_ID: &str = "counter";
+const COUNTER_SLOT: u64 = 0;
+
+// Emitted by every counter call; the arguments stay private to holders of
+// the counter's viewing key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CounterCalled {
+    pub args: Vec<u64>,
+}
+
+impl ContractEvent for CounterCalled {
+    const TOPIC: &'static str = "CounterCalled(u64[])";
+    
+    fn encode(&self) -> Vec<u8> {
+        self.args.iter().flat_map(|arg| arg.to_le_bytes()).collect()
+    }
+    
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        if !bytes.len().is_multiple_of(8) {
+            return None;
+        }
+        let args = bytes.chunks_exact(8).map(|arg| u64::from_le_bytes(arg.try_into().expect("8-byte chunk"))).collect();
+        Some(Self { args })
+    }
+}
+
+// What a validator node keeps per contract. The value commitment is only
+// tracked for ClientSideZK and hybrid contracts, where proofs are anchored to it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StateCommitment {
+    pub state: [u8; 32],
+    pub value: Option<BaseElement>,
+}
+
+// Model-specific evidence submitted alongside a new encrypted state
+#[derive(Clone)]
+pub enum ExecutionEvidence {
+    // Client's STARK that the value commitment advanced by exactly one
+    ZkProof { transition: CounterTransition, proof: Vec<u8> },
+    // Attestation fresh for the previous state, plus the attested enclave's
+    // signature over the transition
+    Enclave { report: AttestationReport, signature: Signature<MlDsa65>, call: [u8; 32] },
+    // Signatures of the compute parties over the transition
+    MpcTranscript { signatures: Vec<(usize, Signature<MlDsa65>)>, call: [u8; 32] },
+    // Input ciphertext and public call arguments of a homomorphic run;
+    // anyone can re-run the program on them
+    Homomorphic { input: LweCiphertext, args: Vec<u64> },
+    // Enclave evidence and the commitments it computed, accepted at once;
+    // the enclave's STARK that the value commitment advanced by one follows
+    // for whoever challenges. None if the prover missed its deadline.
+    Hybrid {
+        report: AttestationReport,
+        signature: Signature<MlDsa65>,
+        call: [u8; 32],
+        transition: CounterTransition,
+        proof: Option<Vec<u8>>,
+    },
+}
+
+impl ExecutionEvidence {
+    pub fn size_bytes(&self) -> usize {
+        match self {
+            ExecutionEvidence::ZkProof { proof, .. } => 2 * 16 + proof.len(),
+            ExecutionEvidence::Enclave { report, signature, .. } => report.size_bytes() + signature.encode().len() + 32,
+            ExecutionEvidence::MpcTranscript { signatures, .. } => {
+                signatures.iter().map(|(_, signature)| 8 + signature.encode().len()).sum::<usize>() + 32
+            }
+            ExecutionEvidence::Homomorphic { input, args } => input.size_bytes() + 8 * args.len(),
+            ExecutionEvidence::Hybrid { report, signature, proof, .. } => {
+                report.size_bytes() + signature.encode().len() + 32 + 2 * 16 + proof.as_ref().map_or(0, Vec::len)
+            }
+        }
+    }
+}
+
+// Why an execution produced no transition. Validators reject with
+// Rejection; executors fail with this.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExecutionError {
+    DecryptFailed,     // wrong key, or a corrupted ciphertext or padding
+    MalformedState,    // opens, but is not a state or call the contract can read
+    AttestationFailed, // no attested enclave holding the contract key
+    InsufficientShares { held: usize, needed: usize },
+    ProofFailed,       // no proof for this transition
+    ProverTimeout { deadline_ms: u64 },
+    Program(VmError),
+    OutOfGas { limit: u64 },
+    Mpc(MpcError),
+    NotDeployed,       // model needs a deployment step that has not run
+    NoPendingCall,     // optimistic executor posted with no call requested
+}
+
+impl std::fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExecutionError::DecryptFailed => write!(f, "state does not decrypt under the key"),
+            ExecutionError::MalformedState => write!(f, "decrypted state is not a counter value"),
+            ExecutionError::AttestationFailed => write!(f, "enclave attestation failed"),
+            ExecutionError::InsufficientShares { held, needed } => {
+                write!(f, "{} shares held, {} needed", held, needed)
+            }
+            ExecutionError::ProofFailed => write!(f, "no proof for this transition"),
+            ExecutionError::ProverTimeout { deadline_ms } => write!(f, "prover missed its {}ms deadline", deadline_ms),
+            ExecutionError::Program(error) => write!(f, "contract failed: {}", error),
+            ExecutionError::OutOfGas { limit } => write!(f, "out of gas (limit {})", limit),
+            ExecutionError::Mpc(error) => write!(f, "MPC failed: {}", error),
+            ExecutionError::NotDeployed => write!(f, "model not deployed for this contract"),
+            ExecutionError::NoPendingCall => write!(f, "no call requested"),
+        }
+    }
+}
+
+impl From<MpcError> for ExecutionError {
+    fn from(error: MpcError) -> Self {
+        match error {
+            MpcError::InsufficientShares { held, needed } => ExecutionError::InsufficientShares { held, needed },
+            error => ExecutionError::Mpc(error),
+        }
+    }
+}
+
+// How long a client waits on its prover before giving up on the call,
+// unless the contract sets its own (EncryptedCounter::with_prover_deadline).
+// Some 50× an optimized prover on an idle machine.
+pub const DEFAULT_PROVER_DEADLINE: Duration = Duration::from_millis(500);
+
+// Failures the fault-injection mode forces on a single call
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InjectedFault {
+    CorruptedCiphertext,  // a bit flipped in the state the executor decrypts
+    ExpiredAttestation,   // the enclave answers with a report for an old nonce
+    PartyDropout,         // MPC parties drop until fewer than the threshold are online
+    ProverTimeout,        // the STARK prover stalls past its deadline
+}
+
+impl InjectedFault {
+    pub const ALL: [InjectedFault; 4] = [
+        InjectedFault::CorruptedCiphertext,
+        InjectedFault::ExpiredAttestation,
+        InjectedFault::PartyDropout,
+        InjectedFault::ProverTimeout,
+    ];
+    
+    // Models with a mechanism for the fault. LWE ciphertexts carry no
+    // integrity check, so a corrupted FHE state is not a failure the
+    // evaluator can see; MPC decrypts the state only on its input round. A
+    // stalled hybrid prover leaves the call standing, open to challenge.
+    pub fn applies_to(self, model: ExecutionModel) -> bool {
+        match self {
+            InjectedFault::CorruptedCiphertext => !matches!(model, ExecutionModel::FHEBased),
+            InjectedFault::ExpiredAttestation => matches!(model, ExecutionModel::TEEBased | ExecutionModel::HybridTEEZK),
+            InjectedFault::PartyDropout => matches!(model, ExecutionModel::MPCBased),
+            InjectedFault::ProverTimeout => matches!(model, ExecutionModel::ClientSideZK),
+        }
+    }
+    
+    // Whether `error` is the failure this fault should cause
+    pub fn explains(self, error: &ExecutionError) -> bool {
+        matches!(
+            (self, error),
+            (InjectedFault::CorruptedCiphertext, ExecutionError::DecryptFailed)
+                | (InjectedFault::ExpiredAttestation, ExecutionError::AttestationFailed)
+                | (InjectedFault::PartyDropout, ExecutionError::InsufficientShares { .. })
+                | (InjectedFault::ProverTimeout, ExecutionError::ProverTimeout { .. })
+        )
+    }
+    
+    fn corrupt(state: &mut EncryptedState) {
+        if let Some(byte) = state.ciphertext.last_mut() {
+            *byte ^= 1;
+        }
+    }
+}
+
+// A slot word or call argument: exactly 8 bytes, little-endian
+fn decode_word(bytes: &[u8]) -> Result<u64, ExecutionError> {
+    Ok(u64::from_le_bytes(bytes.try_into().map_err(|_| ExecutionError::MalformedState)?))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Rejection {
+    CommitmentMismatch,
+    StalePrevious,
+    InvalidProof,
+    InvalidAttestation,
+    InvalidSignature,
+    InsufficientSignatures { valid: usize, threshold: usize },
+    UnsupportedEvidence,
+    StorageFailed,
+    WindowClosed,
+    InsufficientBond { held: u64, needed: u64 },
+    MissingProof,
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::CommitmentMismatch => write!(f, "state commitment does not match ciphertext"),
+            Rejection::StalePrevious => write!(f, "evidence is not for the current on-chain state"),
+            Rejection::InvalidProof => write!(f, "STARK proof does not verify"),
+            Rejection::InvalidAttestation => write!(f, "attestation report rejected"),
+            Rejection::InvalidSignature => write!(f, "transition signature does not verify"),
+            Rejection::InsufficientSignatures { valid, threshold } => {
+                write!(f, "{} valid party signatures, {} required", valid, threshold)
+            }
+            Rejection::UnsupportedEvidence => write!(f, "validator is not configured for this evidence"),
+            Rejection::StorageFailed => write!(f, "accepted state could not be persisted"),
+            Rejection::WindowClosed => write!(f, "challenge window has closed"),
+            Rejection::InsufficientBond { held, needed } => write!(f, "bond of {} needed, {} held", needed, held),
+            Rejection::MissingProof => write!(f, "no proof for the challenged transition"),
+        }
+    }
+}
+
+// Node-side check of a state transition. Holds only public material: the
+// pinned platform key and measurement, and the MPC committee's keys.
+pub struct TransitionValidator {
+    contract_id: String,
+    platform_key: Option<VerifyingKey<MlDsa65>>,
+    expected_measurement: [u8; 32],
+    committee_keys: Vec<VerifyingKey<MlDsa65>>,
+    committee_threshold: usize,
+    // Contract bytecode, for re-running homomorphic evaluations
+    program: Option<Program>,
+}
+
+impl TransitionValidator {
+    pub fn new(contract_id: &str) -> Self {
+        Self {
+            contract_id: contract_id.to_string(),
+            platform_key: None,
+            expected_measurement: [0u8; 32],
+            committee_keys: Vec::new(),
+            committee_threshold: 0,
+            program: None,
+        }
+    }
+    
+    pub fn with_platform(mut self, platform_key: VerifyingKey<MlDsa65>, expected_measurement: [u8; 32]) -> Self {
+        self.platform_key = Some(platform_key);
+        self.expected_measurement = expected_measurement;
+        self
+    }
+    
+    pub fn with_committee(mut self, keys: Vec<VerifyingKey<MlDsa65>>, threshold: usize) -> Self {
+        self.committee_keys = keys;
+        self.committee_threshold = threshold;
+        self
+    }
+    
+    pub fn with_program(mut self, program: Program) -> Self {
+        self.program = Some(program);
+        self
+    }
+    
+    // Accept the new state and return the commitment to store, or reject
+    pub fn validate(
+        &self,
+        previous: &StateCommitment,
+        new_state: &EncryptedState,
+        evidence: &ExecutionEvidence,
+    ) -> Result<StateCommitment, Rejection> {
+        let _phase = Phase::Verify.span().entered();
+        if !new_state.commitment_is_valid() {
+            return Err(Rejection::CommitmentMismatch);
+        }
+        let payload = |call| transition_payload(&self.contract_id, &previous.state, &new_state.commitment, call);
+        
+        match evidence {
+            // The commitments are bound to these ciphertexts. Whether the new
+            // one holds the committed value only a key holder can check; a
+            // client that writes anything else locks itself out.
+            ExecutionEvidence::ZkProof { transition, proof } => {
+                if previous.value != Some(transition.old_commitment) || transition.old_state != previous.state {
+                    return Err(Rejection::StalePrevious);
+                }
+                if transition.new_state != new_state.commitment {
+                    return Err(Rejection::CommitmentMismatch);
+                }
+                if !verify_counter_transition(*transition, proof) {
+                    return Err(Rejection::InvalidProof);
+                }
+                Ok(StateCommitment { state: new_state.commitment, value: Some(transition.new_commitment) })
+            }
+            // Freshness: the report's nonce is the previous state commitment
+            ExecutionEvidence::Enclave { report, signature, call } => {
+                let platform_key = self.platform_key.as_ref().ok_or(Rejection::UnsupportedEvidence)?;
+                if report.nonce != previous.state {
+                    return Err(Rejection::StalePrevious);
+                }
+                if !verify_attestation(report, platform_key, &self.expected_measurement, &previous.state) {
+                    return Err(Rejection::InvalidAttestation);
+                }
+                report.signing_key.verify(&payload(call), signature).map_err(|_| Rejection::InvalidSignature)?;
+                Ok(StateCommitment { state: new_state.commitment, value: None })
+            }
+            // Count distinct parties with a valid signature; t of them must agree
+            ExecutionEvidence::MpcTranscript { signatures, call } => {
+                if self.committee_threshold == 0 {
+                    return Err(Rejection::UnsupportedEvidence);
+                }
+                let payload = payload(call);
+                let mut signed = vec![false; self.committee_keys.len()];
+                for (party, signature) in signatures {
+                    if let Some(key) = self.committee_keys.get(*party) {
+                        signed[*party] |= key.verify(&payload, signature).is_ok();
+                    }
+                }
+                let valid = signed.iter().filter(|&&ok| ok).count();
+                if valid < self.committee_threshold {
+                    return Err(Rejection::InsufficientSignatures { valid, threshold: self.committee_threshold });
+                }
+                Ok(StateCommitment { state: new_state.commitment, value: None })
+            }
+            // The evaluation is deterministic: re-run the program and compare
+            ExecutionEvidence::Homomorphic { input, args } => {
+                let program = self.program.as_ref().ok_or(Rejection::UnsupportedEvidence)?;
+                if EncryptedState::from_ciphertext(input.to_bytes()).commitment != previous.state {
+                    return Err(Rejection::StalePrevious);
+                }
+                let (output, _) = run_on_slot(program, &(), COUNTER_SLOT, input.clone(), args).map_err(|_| Rejection::InvalidProof)?;
+                if EncryptedState::from_ciphertext(output.to_bytes()).commitment != new_state.commitment {
+                    return Err(Rejection::CommitmentMismatch);
+                }
+                Ok(StateCommitment { state: new_state.commitment, value: None })
+            }
+            // Optimistic: the enclave's word for the state and the
+            // commitments; the proof is owed on challenge, see `challenge`
+            ExecutionEvidence::Hybrid { report, signature, call, transition, .. } => {
+                if previous.value != Some(transition.old_commitment) || transition.old_state != previous.state {
+                    return Err(Rejection::StalePrevious);
+                }
+                if transition.new_state != new_state.commitment {
+                    return Err(Rejection::CommitmentMismatch);
+                }
+                let enclave = ExecutionEvidence::Enclave { report: report.clone(), signature: signature.clone(), call: *call };
+                let accepted = self.validate(previous, new_state, &enclave)?;
+                Ok(StateCommitment { value: Some(transition.new_commitment), ..accepted })
+            }
+        }
+    }
+    
+    // The lazy half of a hybrid transition, run by anyone who challenges it
+    // within HYBRID_CHALLENGE_WINDOW: check the STARK that `validate` took
+    // on trust. It shows the value commitment in the new state advanced by
+    // one; a transition without a proof, or with a bad one, is reverted.
+    pub fn challenge(&self, evidence: &ExecutionEvidence) -> Result<(), Rejection> {
+        let _phase = Phase::Verify.span().entered();
+        match evidence {
+            ExecutionEvidence::Hybrid { transition, proof, .. } => {
+                let proof = proof.as_ref().ok_or(Rejection::MissingProof)?;
+                verify_counter_transition(*transition, proof).then_some(()).ok_or(Rejection::InvalidProof)
+            }
+            _ => Err(Rejection::UnsupportedEvidence),
+        }
+    }
+}
+
+// Counter state as its executors see it before a transition: the on-chain
+// state plus what they keep next to it (the ZK commitment blinding, the MPC
+// parties' shares). A transition is computed from a snapshot and applied
+// only if that snapshot is still current.
+#[derive(Clone)]
+pub struct CounterSnapshot {
+    // Contract storage; the counter lives in COUNTER_SLOT
+    pub store: EncryptedStore,
+    // On-chain commitment to the plaintext value, bound to the state that
+    // holds it. Every transition that changes a readable value commits to
+    // it afresh; shared and LWE values when the owner reveals them.
+    pub value_commitment: BaseElement,
+    // Live authenticated shares held by the MPC parties, input once by the owner
+    mpc_shares: Option<AuthSharing>,
+    // Counter under LWE once the owner has moved it there; the slot holds its bytes
+    fhe_value: Option<LweCiphertext>,
+    // Emitted by the transition that produced this snapshot
+    pub events: Vec<EncryptedEvent>,
+}
+
+impl CounterSnapshot {
+    pub fn state(&self) -> &EncryptedState {
+        self.store.get(COUNTER_SLOT).expect("counter slot is written at deploy")
+    }
+    
+    pub fn commitment(&self) -> StateCommitment {
+        StateCommitment { state: self.state().commitment, value: Some(self.value_commitment) }
+    }
+    
+    // Key holder's commitment to a value it has just written
+    fn recommit(&mut self, value: u64, key: &ViewingKey) {
+        let state = self.state();
+        let blinding = state.value_blinding(key).expect("key holders write states under their own key");
+        self.value_commitment = value_commitment(value, blinding, &state.commitment);
+    }
+}
+
+// Simple contract: encrypted counter
+pub struct EncryptedCounter {
+    current: CounterSnapshot,
+    // Contract bytecode and the arguments of each call
+    program: Program,
+    call_args: Vec<u64>,
+    // What each call may spend, priced by the schedule
+    gas_schedule: GasSchedule,
+    gas_limit: u64,
+    // Padding for the states key holders write
+    padding: PaddingPolicy,
+    // Evidence for the last transition, as submitted to validators
+    evidence: Option<ExecutionEvidence>,
+    // Events of every applied transition, in order
+    event_log: Vec<EncryptedEvent>,
+    mpc_dealer: TrustedDealer,
+    mpc_network: NetworkConfig,
+    mpc_faults: Vec<(usize, MpcFault)>,
+    mpc_committee: MpcCommittee,
+    // How long a call waits on the STARK prover
+    prover_deadline: Duration,
+    // Key of the epoch the state was last rotated to; until then states
+    // are sealed at epoch 0, under keys derived from the contract key
+    epoch_key: Option<ViewingKey>,
+    // Enclave running this contract, launched on first TEE use
+    platform: Arc<SimulatedPlatform>,
+    enclave: Option<Enclave>,
+}
+
+impl EncryptedCounter {
+    pub fn new(initial_value: u64, key: &[u8]) -> Self {
+        let current = CounterSnapshot {
+            store: EncryptedStore::new(),
+            value_commitment: BaseElement::ZERO,
+            mpc_shares: None,
+            fhe_value: None,
+            events: Vec::new(),
+        };
+        let mut counter = Self {
+            current,
+            program: Program::decode(COUNTER_CONTRACT_CODE).expect("counter bytecode is well-formed"),
+            call_args: vec![1],
+            gas_schedule: GasSchedule::default(),
+            gas_limit: DEFAULT_GAS_LIMIT,
+            padding: PaddingPolicy::None,
+            evidence: None,
+            event_log: Vec::new(),
+            mpc_dealer: TrustedDealer::new(3, 5), // 3-of-5
+            mpc_network: NetworkConfig::lan(),
+            mpc_faults: Vec::new(),
+            mpc_committee: MpcCommittee::new(3, 5),
+            prover_deadline: DEFAULT_PROVER_DEADLINE,
+            epoch_key: None,
+            platform: Arc::new(SimulatedPlatform::new()),
+            enclave: None,
+        };
+        let state = counter.seal(key, &initial_value.to_le_bytes(), &PaddingPolicy::None);
+        counter.current.store.write(COUNTER_SLOT, state);
+        counter.current.recommit(initial_value, &counter.state_key(key));
+        counter
+    }
+    
+    // Run other bytecode in place of the counter contract. Programs see one
+    // slot, holding the counter value; the STARK covers +1 only, so
+    // ClientSideZK fails for anything else.
+    pub fn with_program(mut self, program: Program, call_args: &[u64]) -> Self {
+        self.program = program;
+        self.call_args = call_args.to_vec();
+        self
+    }
+    
+    // At deploy: re-seals the initial state under `padding`, so no state of
+    // the contract shows its size
+    pub fn with_padding(mut self, padding: PaddingPolicy, key: &[u8]) -> Self {
+        let viewing_key = self.state_key(key);
+        if let Some(plaintext) = self.current.state().open(&viewing_key) {
+            let state = self.seal(key, &plaintext, &padding);
+            self.current.store.write(COUNTER_SLOT, state);
+            if let Ok(value) = decode_word(&plaintext) {
+                self.current.recommit(value, &viewing_key);
+            }
+        }
+        self.padding = padding;
+        self
+    }
+    
+    pub fn with_gas(mut self, schedule: GasSchedule, limit: u64) -> Self {
+        self.gas_schedule = schedule;
+        self.gas_limit = limit;
+        self
+    }
+    
+    pub fn with_mpc_network(mut self, config: NetworkConfig) -> Self {
+        self.mpc_network = config;
+        self
+    }
+    
+    // Fault-injection mode for the MPC parties
+    pub fn with_mpc_faults(mut self, faults: &[(usize, MpcFault)]) -> Self {
+        self.mpc_faults = faults.to_vec();
+        self
+    }
+    
+    pub fn with_prover_deadline(mut self, deadline: Duration) -> Self {
+        self.prover_deadline = deadline;
+        self
+    }
+    
+    // How long a stalled prover sleeps before it starts (fault injection)
+    fn prover_stall(&self) -> Duration {
+        2 * self.prover_deadline
+    }
+    
+    pub fn value_commitment(&self) -> BaseElement {
+        self.current.value_commitment
+    }
+    
+    pub fn state(&self) -> &EncryptedState {
+        self.current.state()
+    }
+    
+    pub fn state_commitment(&self) -> StateCommitment {
+        self.current.commitment()
+    }
+    
+    pub fn snapshot(&self) -> CounterSnapshot {
+        self.current.clone()
+    }
+    
+    // Evidence for the last increment, for TransitionValidator::validate
+    pub fn evidence(&self) -> Option<&ExecutionEvidence> {
+        self.evidence.as_ref()
+    }
+    
+    pub fn events(&self) -> &[EncryptedEvent] {
+        &self.event_log
+    }
+    
+    // The counter's viewing key: states and events are sealed under keys
+    // derived from it, so exports of it (or of a subtree) read them
+    pub fn viewing_key(key: &[u8]) -> ViewingKey {
+        ViewingKey::master(key).child(KeySegment::Contract(COUNTER_CONTRACT_ID.to_string()))
+    }
+    
+    // Key the counter's states open under: the current epoch's once
+    // rotated, else the contract key
+    fn state_key(&self, key: &[u8]) -> ViewingKey {
+        self.epoch_key.clone().unwrap_or_else(|| Self::viewing_key(key))
+    }
+    
+    // Counter state as the key holder writes it: contract, the store's key
+    // epoch, slot
+    pub fn seal(&self, key: &[u8], plaintext: &[u8], padding: &PaddingPolicy) -> EncryptedState {
+        let path = KeyPath::contract(COUNTER_CONTRACT_ID).epoch(self.current.store.key_epoch()).slot(COUNTER_SLOT);
+        let slot_key = self.state_key(key).derive(&path).expect("the counter holds its current epoch's key");
+        EncryptedState::under(&slot_key, plaintext, padding)
+    }
+    
+    // Rotates the counter's state to the next key epoch in its enclave, for
+    // `owner`. Applied once it verifies against the pinned platform key;
+    // later states are sealed at the new epoch, under the key the owner
+    // opens from the rotation, which the old keys cannot derive.
+    pub fn rotate_keys(&mut self, key: &[u8], owner: &<MlKem512 as KemCore>::DecapsulationKey) -> Result<KeyRotation, ExecutionError> {
+        if !self.prepare_enclave(key) {
+            return Err(ExecutionError::AttestationFailed);
+        }
+        let value = self.decrypt_value(self.current.state(), key)?;
+        let states = vec![self.current.state().clone()];
+        let to_epoch = self.current.store.key_epoch() + 1;
+        let enclave = self.enclave.as_mut().ok_or(ExecutionError::AttestationFailed)?;
+        let rotation = enclave
+            .rotate_keys(COUNTER_CONTRACT_ID, &states, to_epoch, owner.encapsulation_key())
+            .ok_or(ExecutionError::AttestationFailed)?;
+        let epoch_key = rotation.open_epoch_key(owner).ok_or(ExecutionError::DecryptFailed)?;
+        if !rotation.verify(&states, &self.platform.verifying_key(), &self.program.measurement())
+            || !self.current.store.apply_rotation(&rotation)
+        {
+            return Err(ExecutionError::AttestationFailed);
+        }
+        self.current.recommit(value, &epoch_key);
+        self.epoch_key = Some(epoch_key);
+        Ok(rotation)
+    }
+    
+    // The call as its CounterCalled event: only keys that read the calls
+    // can read its arguments
+    fn seal_call(&self, key: &[u8]) -> Option<EncryptedEvent> {
+        EncryptedEvent::emit(&Self::viewing_key(key), &CounterCalled { args: self.call_args.clone() })
+    }
+    
+    // Public material a validator pins for this contract
+    pub fn validator(&self) -> TransitionValidator {
+        TransitionValidator::new(COUNTER_CONTRACT_ID)
+            .with_platform(self.platform.verifying_key(), self.program.measurement())
+            .with_committee(self.mpc_committee.verifying_keys(), self.mpc_committee.threshold())
+            .with_program(self.program.clone())
+    }
+    
+    // Same program and call arguments on an unencrypted value, for the
+    // baseline; wraps like the plaintext VM
+    pub fn plaintext_call(&self, value: u64) -> Result<u64, VmError> {
+        run_on_slot(&self.program, &(), COUNTER_SLOT, value, &self.call_args).map(|(value, _)| value)
+    }
+    
+    // Increment counter (different execution models). On failure the state
+    // is left as it was.
+    pub fn increment(&mut self, model: ExecutionModel, key: &[u8]) -> Result<ExecutionResult, ExecutionError> {
+        self.prepare(model, key)?;
+        let (result, next) = self.execute(model, &self.current, key);
+        let (next, evidence) = next?;
+        self.event_log.extend(next.events.iter().cloned());
+        self.current = next;
+        self.evidence = Some(evidence);
+        Ok(result)
+    }
+    
+    // One increment against `snapshot`, without applying it. Returns the next
+    // snapshot and its evidence on success; the result reports either way.
+    pub fn execute(
+        &self,
+        model: ExecutionModel,
+        snapshot: &CounterSnapshot,
+        key: &[u8],
+    ) -> (ExecutionResult, Result<(CounterSnapshot, ExecutionEvidence), ExecutionError>) {
+        self.execute_with_fault(model, snapshot, key, None)
+    }
+    
+    // Same, with `fault` forced on the call; see FaultInjector
+    pub fn execute_with_fault(
+        &self,
+        model: ExecutionModel,
+        snapshot: &CounterSnapshot,
+        key: &[u8],
+        fault: Option<InjectedFault>,
+    ) -> (ExecutionResult, Result<(CounterSnapshot, ExecutionEvidence), ExecutionError>) {
+        // The event carries only the call's args, so the caller seals it on
+        // the client and submits it with the call. No executor needs the
+        // contract key for it: the FHE evaluator, which holds none, only
+        // attaches it to the state it commits.
+        self.execute_call(model, snapshot, key, self.seal_call(key), fault)
+    }
+    
+    // Runs the call the user sealed as `call`. Enclaves and MPC parties sign
+    // over its commitment, binding their transition to this call.
+    fn execute_call(
+        &self,
+        model: ExecutionModel,
+        snapshot: &CounterSnapshot,
+        key: &[u8],
+        call: Option<EncryptedEvent>,
+        fault: Option<InjectedFault>,
+    ) -> (ExecutionResult, Result<(CounterSnapshot, ExecutionEvidence), ExecutionError>) {
+        let (mut result, next) = match model {
+            ExecutionModel::ClientSideZK => self.execute_client_zk(snapshot, key, fault),
+            ExecutionModel::TEEBased => self.execute_tee(snapshot, call.as_ref(), fault),
+            ExecutionModel::MPCBased => self.execute_mpc(snapshot, key, call.as_ref(), fault),
+            ExecutionModel::FHEBased => self.execute_fhe(snapshot),
+            ExecutionModel::HybridTEEZK => self.execute_hybrid(snapshot, call.as_ref(), fault),
+        };
+        let events: Vec<EncryptedEvent> = call.into_iter().collect();
+        let next = next.map(|(mut next, evidence)| {
+            result.event_commitments = events.iter().map(EncryptedEvent::commitment).collect();
+            next.events = events;
+            (next, evidence)
+        });
+        (result, next)
+    }
+    
+    // Model 1: Client-side execution with ZK proof
+    fn execute_client_zk(&self, snapshot: &CounterSnapshot, key: &[u8], fault: Option<InjectedFault>) -> (ExecutionResult, Result<(CounterSnapshot, ExecutionEvidence), ExecutionError>) {
+        let mut gas = GasMeter::new(self.gas_schedule, self.gas_limit);
+        let (next, timings) = PhaseTimings::record(|| {
+            // Client reads the slot and decrypts locally
+            let mut store = snapshot.store.fork();
+            gas.charge(GasOp::StorageRead, 1)?;
+            let mut value = Phase::Decrypt.span().in_scope(|| {
+                let (state, _) = store.read(COUNTER_SLOT);
+                let mut state = state.ok_or(ExecutionError::NotDeployed)?;
+                if fault == Some(InjectedFault::CorruptedCiphertext) {
+                    InjectedFault::corrupt(&mut state);
+                }
+                self.decrypt_value(&state, key)
+            })?;
+            
+            // Execute the contract
+            let old_value = value;
+            value = Phase::Compute.span().in_scope(|| {
+                gas.run(&self.program, |program| run_on_slot(program, &(), COUNTER_SLOT, value, &self.call_args))
+            })?;
+            
+            // Re-encrypt into the slot
+            gas.charge(GasOp::StorageWrite, 1)?;
+            let state = Phase::Encrypt.span().in_scope(|| {
+                let state = self.seal(key, &value.to_le_bytes(), &self.padding);
+                store.write(COUNTER_SLOT, state.clone());
+                state
+            });
+            
+            // Prove new commitment = commit(old + 1) against the on-chain commitment
+            gas.charge(GasOp::ProofGeneration, 1)?;
+            let _phase = Phase::Prove.span().entered();
+            let stall = (fault == Some(InjectedFault::ProverTimeout)).then_some(self.prover_stall());
+            let (transition, proof) =
+                Self::generate_zk_proof(snapshot, &state, &self.state_key(key), (old_value, value), self.prover_deadline, stall)?;
+            let next = CounterSnapshot {
+                store,
+                value_commitment: transition.new_commitment,
+                mpc_shares: snapshot.mpc_shares.clone(),
+                fhe_value: snapshot.fhe_value.clone(),
+                events: Vec::new(),
+            };
+            Ok((next, ExecutionEvidence::ZkProof { transition, proof }))
+        });
+        
+        let size = next.as_ref().map_or(0, |(_, evidence)| evidence.size_bytes());
+        let slots = next.as_ref().map_or(0, |(next, _)| next.store.touched());
+        let result = ExecutionResult::from_phases(ExecutionModel::ClientSideZK, next.is_ok(), timings, size, slots, None);
+        (result.with_gas(&gas).with_error(next.as_ref().err()), next)
+    }
+    
+    // Deployment steps `model` needs before its first call, if any
+    pub fn prepare(&mut self, model: ExecutionModel, key: &[u8]) -> Result<(), ExecutionError> {
+        match model {
+            ExecutionModel::TEEBased | ExecutionModel::HybridTEEZK if !self.prepare_enclave(key) => {
+                Err(ExecutionError::AttestationFailed)
+            }
+            ExecutionModel::FHEBased => self.prepare_fhe(key),
+            _ => Ok(()),
+        }
+    }
+    
+    // Deployment, not per-call cost: launch the enclave on the platform and
+    // provision the contract key over an attested channel
+    pub fn prepare_enclave(&mut self, key: &[u8]) -> bool {
+        let (platform, platform_key) = (self.platform.clone(), self.platform.verifying_key());
+        let enclave = self.enclave.get_or_insert_with(|| Enclave::launch(platform, &self.program.encode()));
+        enclave.has_key(COUNTER_CONTRACT_ID)
+            || Self::provision(enclave, &platform_key, &self.program.measurement(), key, self.epoch_key.as_ref())
+    }
+    
+    // A further enclave running the contract on the same platform, e.g. for
+    // a verifier re-executing transitions
+    pub fn attested_enclave(&self, key: &[u8]) -> Option<Enclave> {
+        let mut enclave = Enclave::launch(self.platform.clone(), &self.program.encode());
+        Self::provision(&mut enclave, &self.platform.verifying_key(), &self.program.measurement(), key, self.epoch_key.as_ref())
+            .then_some(enclave)
+    }
+    
+    // Checked against the platform key the contract pinned, never one the
+    // enclave reports about itself. After a rotation the enclave also needs
+    // the current epoch's key, which the contract key cannot derive.
+    fn provision(
+        enclave: &mut Enclave,
+        platform_key: &VerifyingKey<MlDsa65>,
+        measurement: &[u8; 32],
+        key: &[u8],
+        epoch_key: Option<&ViewingKey>,
+    ) -> bool {
+        let mut nonce = [0u8; 32];
+        rand::thread_rng().fill(&mut nonce);
+        let report = enclave.attest(nonce);
+        let contract_key = Self::viewing_key(key).export_scoped(&KeyPath::contract(COUNTER_CONTRACT_ID));
+        verify_attestation(&report, platform_key, measurement, &nonce)
+            && contract_key.is_some_and(|contract_key| {
+                enclave.provision_key(COUNTER_CONTRACT_ID, &encrypt_input_for_enclave(&report, &contract_key))
+            })
+            && epoch_key.is_none_or(|epoch_key| {
+                let epoch_id = KeyRotation::epoch_id(COUNTER_CONTRACT_ID, epoch_key.path().key_epoch().unwrap_or(0));
+                epoch_key.export_scoped(epoch_key.path()).is_some_and(|exported| {
+                    enclave.provision_key(&epoch_id, &encrypt_input_for_enclave(&report, &exported))
+                })
+            })
+    }
+    
+    // Model 2: TEE-based execution
+    fn execute_tee(
+        &self,
+        snapshot: &CounterSnapshot,
+        call: Option<&EncryptedEvent>,
+        fault: Option<InjectedFault>,
+    ) -> (ExecutionResult, Result<(CounterSnapshot, ExecutionEvidence), ExecutionError>) {
+        let mut gas = GasMeter::new(self.gas_schedule, self.gas_limit);
+        let (next, timings) = PhaseTimings::record(|| {
+            let call = call.ok_or(ExecutionError::DecryptFailed)?;
+            let (store, report, signature) = self.run_in_enclave(snapshot, call, fault, &mut gas)?;
+            
+            // The enclave commits to the new value in the new state
+            let value_commitment = Phase::Prove.span().in_scope(|| {
+                let enclave = self.enclave.as_ref().ok_or(ExecutionError::AttestationFailed)?;
+                let new_state = store.get(COUNTER_SLOT).ok_or(ExecutionError::NotDeployed)?;
+                enclave.commit_value(COUNTER_CONTRACT_ID, new_state)
+            })?;
+            let evidence = ExecutionEvidence::Enclave { report, signature, call: call.commitment() };
+            Ok((CounterSnapshot { store, value_commitment, ..snapshot.clone() }, evidence))
+        });
+        
+        let size = next.as_ref().map_or(0, |(_, evidence)| evidence.size_bytes());
+        let slots = next.as_ref().map_or(0, |(next, _)| next.store.touched());
+        let result = ExecutionResult::from_phases(ExecutionModel::TEEBased, next.is_ok(), timings, size, slots, None);
+        (result.with_gas(&gas).with_error(next.as_ref().err()), next)
+    }
+    
+    // Attested call into the enclave: the new store, a report fresh for the
+    // previous state, and the enclave's signature over the transition and `call`
+    fn run_in_enclave(
+        &self,
+        snapshot: &CounterSnapshot,
+        call: &EncryptedEvent,
+        fault: Option<InjectedFault>,
+        gas: &mut GasMeter,
+    ) -> Result<(EncryptedStore, AttestationReport, Signature<MlDsa65>), ExecutionError> {
+        let enclave = self.enclave.as_ref().ok_or(ExecutionError::AttestationFailed)?;
+        
+        // Client verifies a fresh attestation report before sending anything
+        let (report, attested) = Phase::Attest.span().in_scope(|| {
+            let mut nonce = [0u8; 32];
+            rand::thread_rng().fill(&mut nonce);
+            let report = match fault {
+                Some(InjectedFault::ExpiredAttestation) => enclave.attest([0; 32]),
+                _ => enclave.attest(nonce),
+            };
+            let attested = verify_attestation(&report, &self.platform.verifying_key(), &self.program.measurement(), &nonce);
+            (report, attested)
+        });
+        if !attested {
+            return Err(ExecutionError::AttestationFailed);
+        }
+        
+        // Client encrypts the call arguments to the attested enclave
+        gas.charge(GasOp::KemEncapsulation, 1)?;
+        let input = Phase::Encrypt.span().in_scope(|| {
+            let args: Vec<u8> = self.call_args.iter().flat_map(|arg| arg.to_le_bytes()).collect();
+            encrypt_input_for_enclave(&report, &args)
+        });
+        
+        // Execute inside the enclave (simulated - would use SGX/SEV in production)
+        let mut store = snapshot.store.fork();
+        let signature = Phase::Compute.span().in_scope(|| {
+            gas.charge(GasOp::StorageRead, 1)?;
+            let (previous, _) = store.read(COUNTER_SLOT);
+            let mut previous = previous.ok_or(ExecutionError::NotDeployed)?;
+            if fault == Some(InjectedFault::CorruptedCiphertext) {
+                InjectedFault::corrupt(&mut previous);
+            }
+            let (state, signature) = enclave.execute_program(COUNTER_CONTRACT_ID, COUNTER_SLOT, &previous, &input, call, gas)?;
+            gas.charge(GasOp::StorageWrite, 1)?;
+            store.write(COUNTER_SLOT, state);
+            Ok::<_, ExecutionError>(signature)
+        })?;
+        
+        // Evidence for validators: a report fresh for the previous state
+        gas.charge(GasOp::ProofGeneration, 1)?;
+        let _phase = Phase::Prove.span().entered();
+        Ok((store, enclave.attest(snapshot.state().commitment), signature))
+    }
+    
+    // Model 5: TEE execution, accepted on the enclave's signature and the
+    // value commitment it computed; the enclave proves the step after that,
+    // off the critical path. A prover that misses its deadline costs the
+    // proof, not the call: the transition stands until it is challenged.
+    fn execute_hybrid(
+        &self,
+        snapshot: &CounterSnapshot,
+        call: Option<&EncryptedEvent>,
+        fault: Option<InjectedFault>,
+    ) -> (ExecutionResult, Result<(CounterSnapshot, ExecutionEvidence), ExecutionError>) {
+        let mut gas = GasMeter::new(self.gas_schedule, self.gas_limit);
+        let (mut optimistic, mut proving) = (Duration::ZERO, None);
+        let (next, timings) = PhaseTimings::record(|| {
+            let start = Instant::now();
+            let call = call.ok_or(ExecutionError::DecryptFailed)?;
+            let (mut store, report, signature) = self.run_in_enclave(snapshot, call, fault, &mut gas)?;
+            let enclave = self.enclave.as_ref().ok_or(ExecutionError::AttestationFailed)?;
+            let (new_state, _) = store.read(COUNTER_SLOT);
+            let new_state = new_state.ok_or(ExecutionError::NotDeployed)?;
+            let new_commitment = enclave.commit_value(COUNTER_CONTRACT_ID, &new_state)?;
+            let transition = CounterTransition {
+                old_commitment: snapshot.value_commitment,
+                new_commitment,
+                old_state: snapshot.state().commitment,
+                new_state: new_state.commitment,
+            };
+            optimistic = start.elapsed();
+            
+            // Accepted by now; the STARK a challenger checks comes after
+            gas.charge(GasOp::ProofGeneration, 1)?;
+            let _phase = Phase::Prove.span().entered();
+            let stall = (fault == Some(InjectedFault::ProverTimeout)).then_some(self.prover_stall());
+            let proved = enclave.prove_counter_step(COUNTER_CONTRACT_ID, snapshot, &new_state, self.prover_deadline, stall);
+            let proof = match proved {
+                Ok((proved, proof)) if proved == transition => {
+                    proving = Some(start.elapsed() - optimistic);
+                    Some(proof)
+                }
+                _ => None,
+            };
+            let next = CounterSnapshot { store, value_commitment: new_commitment, ..snapshot.clone() };
+            Ok((next, ExecutionEvidence::Hybrid { report, signature, call: call.commitment(), transition, proof }))
+        });
+        
+        let size = next.as_ref().map_or(0, |(_, evidence)| evidence.size_bytes());
+        let slots = next.as_ref().map_or(0, |(next, _)| next.store.touched());
+        let mut result = ExecutionResult::from_phases(ExecutionModel::HybridTEEZK, next.is_ok(), timings, size, slots, None)
+            .with_gas(&gas)
+            .with_error(next.as_ref().err());
+        if next.is_ok() {
+            result.hybrid_latency = Some(HybridLatency {
+                optimistic_us: optimistic.as_micros() as u64,
+                proof_us: proving.map(|proving| proving.as_micros() as u64),
+            });
+        }
+        (result, next)
+    }
+    
+    // Model 3: MPC-based execution
+    fn execute_mpc(
+        &self,
+        snapshot: &CounterSnapshot,
+        key: &[u8],
+        call: Option<&EncryptedEvent>,
+        fault: Option<InjectedFault>,
+    ) -> (ExecutionResult, Result<(CounterSnapshot, ExecutionEvidence), ExecutionError>) {
+        let mut gas = GasMeter::new(self.gas_schedule, self.gas_limit);
+        let ((next, stats), timings) = PhaseTimings::record(|| {
+            let Some(call) = call.map(EncryptedEvent::commitment) else {
+                return (Err(ExecutionError::DecryptFailed), NetworkStats::default());
+            };
+            // Secret share the state (Shamir's) - only on first use, the
+            // parties keep their shares across increments. The owner inputs the
+            // value through a preprocessed authenticated mask [r].
+            let held = snapshot.mpc_shares.as_ref();
+            let mut store = snapshot.store.fork();
+            let mut network = MpcNetwork::new(self.mpc_network, &self.mpc_dealer).with_faults(&self.mpc_faults);
+            if fault == Some(InjectedFault::PartyDropout) {
+                // All but threshold - 1 parties stop; the client notices
+                // when they miss its acknowledgement round
+                let dropouts: Vec<usize> = (network.threshold - 1..network.parties).collect();
+                network = network.with_dropouts(&dropouts);
+            }
+            
+            // Opening a shared value interpolates from `threshold` shares, so
+            // fewer parties holding one than that can compute nothing
+            let online = held.map_or(network.parties, |held| held.shares.len());
+            if online < network.threshold {
+                let error = ExecutionError::InsufficientShares { held: online, needed: network.threshold };
+                return (Err(error), NetworkStats::default());
+            }
+            let input = match held {
+                Some(_) => None,
+                None => {
+                    let value = gas.charge(GasOp::StorageRead, 1).and_then(|_| {
+                        Phase::Decrypt.span().in_scope(|| {
+                            let (state, _) = store.read(COUNTER_SLOT);
+                            let mut state = state.ok_or(ExecutionError::NotDeployed)?;
+                            if fault == Some(InjectedFault::CorruptedCiphertext) {
+                                InjectedFault::corrupt(&mut state);
+                            }
+                            self.decrypt_value(&state, key)
+                        })
+                    });
+                    let value = match value {
+                        Ok(value) => value,
+                        Err(error) => return (Err(error), NetworkStats::default()),
+                    };
+                    let mask = Phase::Preprocess.span().in_scope(|| self.mpc_dealer.input_mask());
+                    Some((value, mask))
+                }
+            };
+            
+            // Parties run concurrently: receive the masked input if this is the
+            // first increment, then run the contract on their own share. Every
+            // party runs the same steps; the call pays for them once.
+            let program = gas.limited(&self.program);
+            let run = Phase::Compute.span().in_scope(|| {
+                network.run(
+                    |ctx| {
+                        let share = match (held, &input) {
+                            (Some(held), _) => Ok(held.shares[ctx.id].clone()),
+                            (None, Some((_, mask))) => ctx.receive_masked_inputs(&[mask.sharing.shares[ctx.id].clone()]).map(|mut shares| shares.remove(0)),
+                            (None, None) => unreachable!("input is prepared whenever no shares are held"),
+                        };
+                        let output = share.map(|share| run_on_slot(&program, &ctx.share_context(), COUNTER_SLOT, share, &self.call_args));
+                        ctx.acknowledge();
+                        output
+                    },
+                    |ctx| {
+                        if let Some((value, mask)) = &input {
+                            ctx.next_round();
+                            for party in 0..ctx.parties {
+                                ctx.send(party, vec![Fp::new(*value) - mask.value]);
+                            }
+                        }
+                        ctx.await_acknowledgements()
+                    },
+                )
+            });
+            
+            let (outputs, acknowledged, stats) = match run {
+                Ok(run) => run,
+                Err(error) => return (Err(error.into()), NetworkStats::default()),
+            };
+            // Dropped parties surface here, by the acknowledgements they missed
+            let new_shares = match acknowledged.and_then(|_| outputs.into_iter().collect::<Result<Vec<_>, _>>()) {
+                Ok(new_shares) => new_shares,
+                Err(error) => return (Err(error.into()), stats),
+            };
+            let outcome = new_shares.into_iter().collect::<Result<Vec<_>, _>>().map(|runs| {
+                let run = runs.first().map_or(VmRun { steps: 0 }, |(_, run)| *run);
+                (runs.into_iter().map(|(share, _)| share).collect::<Vec<_>>(), run)
+            });
+            let new_shares = match gas.settle(&self.program, outcome).and_then(|shares| {
+                gas.charge(GasOp::MpcRound, stats.rounds)?;
+                Ok(shares)
+            }) {
+                Ok(new_shares) => new_shares,
+                Err(error) => return (Err(error), stats),
+            };
+            
+            // Client mirrors the program's linear steps on its MAC keys
+            let sharing = Phase::Share.span().in_scope(|| {
+                let client_alpha = self.mpc_dealer.client_mac_key();
+                let mut sharing = match (held, input) {
+                    (Some(held), _) => held.clone(),
+                    (None, Some((value, mask))) => {
+                        let mut sharing = mask.sharing;
+                        sharing.client_add_const(Fp::new(value) - mask.value, client_alpha);
+                        sharing
+                    }
+                    (None, None) => unreachable!("input is prepared whenever no shares are held"),
+                };
+                sharing.shares = new_shares;
+                let context = sharing.client_context(client_alpha);
+                let client_run = run_on_slot(&self.program, &context, COUNTER_SLOT, sharing.client_view(), &self.call_args);
+                sharing.client_keys = client_run.map_err(ExecutionError::Program)?.0.keys;
+                Ok(sharing)
+            });
+            
+            // Each party stores its sealed share on-chain and signs the transition.
+            // No reconstruction: the value stays shared until the owner calls reveal_mpc
+            let sharing = match sharing.and_then(|sharing| {
+                gas.charge(GasOp::StorageWrite, 1)?;
+                gas.charge(GasOp::ProofGeneration, 1)?;
+                Ok(sharing)
+            }) {
+                Ok(sharing) => sharing,
+                Err(error) => return (Err(error), stats),
+            };
+            let _phase = Phase::Prove.span().entered();
+            let state = self.mpc_committee.seal_shares(COUNTER_CONTRACT_ID, &sharing.shares);
+            let signatures = (0..sharing.shares.len())
+                .map(|party| {
+                    let signature = self.mpc_committee.sign_transition(
+                        party,
+                        COUNTER_CONTRACT_ID,
+                        &snapshot.state().commitment,
+                        &state.commitment,
+                        &call,
+                    );
+                    (party, signature)
+                })
+                .collect();
+            store.write(COUNTER_SLOT, state);
+            let next = CounterSnapshot { store, mpc_shares: Some(sharing), ..snapshot.clone() };
+            (Ok((next, ExecutionEvidence::MpcTranscript { signatures, call })), stats)
+        });
+        
+        let size = next.as_ref().map_or(0, |(_, evidence)| evidence.size_bytes());
+        let slots = next.as_ref().map_or(0, |(next, _)| next.store.touched());
+        let result = ExecutionResult::from_phases(ExecutionModel::MPCBased, next.is_ok(), timings, size, slots, Some(stats));
+        (result.with_gas(&gas).with_error(next.as_ref().err()), next)
+    }
+    
+    // Deployment, not per-call cost: the owner re-encrypts the value under
+    // LWE once, after which anyone can increment it
+    pub fn prepare_fhe(&mut self, key: &[u8]) -> Result<(), ExecutionError> {
+        if self.current.fhe_value.is_some() {
+            return Ok(());
+        }
+        let value = self.decrypt_value(self.current.state(), key)?;
+        let ciphertext = LweSecretKey::derive(key).encrypt(value);
+        self.current.store.write(COUNTER_SLOT, EncryptedState::from_ciphertext(ciphertext.to_bytes()));
+        self.current.fhe_value = Some(ciphertext);
+        Ok(())
+    }
+    
+    // Model 4: homomorphic evaluation; the evaluator holds no key at all
+    fn execute_fhe(&self, snapshot: &CounterSnapshot) -> (ExecutionResult, Result<(CounterSnapshot, ExecutionEvidence), ExecutionError>) {
+        let mut gas = GasMeter::new(self.gas_schedule, self.gas_limit);
+        let (next, timings) = PhaseTimings::record(|| {
+            let input = snapshot.fhe_value.as_ref().ok_or(ExecutionError::NotDeployed)?;
+            let mut store = snapshot.store.fork();
+            gas.charge(GasOp::StorageRead, 1)?;
+            
+            // Run the contract on the ciphertext and store the result
+            let output = Phase::Compute.span().in_scope(|| {
+                let output = gas.run(&self.program, |program| {
+                    run_on_slot(program, &(), COUNTER_SLOT, input.clone(), &self.call_args)
+                })?;
+                gas.charge(GasOp::StorageWrite, 1)?;
+                store.write(COUNTER_SLOT, EncryptedState::from_ciphertext(output.to_bytes()));
+                Ok::<_, ExecutionError>(output)
+            })?;
+            
+            // Evidence for validators: the input, so they can re-run the program
+            let _phase = Phase::Prove.span().entered();
+            let evidence = ExecutionEvidence::Homomorphic { input: input.clone(), args: self.call_args.clone() };
+            Ok((CounterSnapshot { store, fhe_value: Some(output), ..snapshot.clone() }, evidence))
+        });
+        
+        let size = next.as_ref().map_or(0, |(_, evidence)| evidence.size_bytes());
+        let slots = next.as_ref().map_or(0, |(next, _)| next.store.touched());
+        let mut result = ExecutionResult::from_phases(ExecutionModel::FHEBased, next.is_ok(), timings, size, slots, None)
+            .with_gas(&gas)
+            .with_error(next.as_ref().err());
+        result.lwe_stats = next.as_ref().ok().and_then(|(next, _)| next.fhe_value.as_ref()).map(|output| LweStats {
+            ciphertext_bytes: output.size_bytes(),
+            plaintext_bytes: std::mem::size_of::<u64>(),
+            growth_bytes: output.size_bytes() as i64 - snapshot.fhe_value.as_ref().map_or(0, |input| input.size_bytes() as i64),
+            noise_budget_bits: output.noise_budget_bits(),
+        });
+        (result, next)
+    }
+    
+    // Owner decrypts the LWE counter, writes it back as AES-GCM state and
+    // commits to it
+    pub fn reveal_fhe(&mut self, key: &[u8]) -> Option<u64> {
+        let ciphertext = self.current.fhe_value.take()?;
+        let value = Phase::Decrypt.span().in_scope(|| LweSecretKey::derive(key).decrypt(&ciphertext));
+        Phase::Encrypt.span().in_scope(|| {
+            let state = self.seal(key, &value.to_le_bytes(), &self.padding);
+            self.current.store.write(COUNTER_SLOT, state);
+            self.current.recommit(value, &self.state_key(key));
+        });
+        Some(value)
+    }
+    
+    // The prover runs on its own thread and is abandoned once it misses
+    // `deadline`; see spawn_prover. `stall` makes the prover sleep before it
+    // starts (fault injection). `key` opens both states and so yields both
+    // blindings.
+    fn generate_zk_proof(
+        snapshot: &CounterSnapshot,
+        new_state: &EncryptedState,
+        key: &ViewingKey,
+        (old_value, new_value): (u64, u64),
+        deadline: Duration,
+        stall: Option<Duration>,
+    ) -> Result<(CounterTransition, Vec<u8>), ExecutionError> {
+        if old_value.checked_add(1) != Some(new_value) {
+            return Err(ExecutionError::ProofFailed);
+        }
+        let old_state = snapshot.state();
+        let blinding = |state: &EncryptedState| state.value_blinding(key).ok_or(ExecutionError::DecryptFailed);
+        let (old_blinding, new_blinding) = (blinding(old_state)?, blinding(new_state)?);
+        let (old_commitment, new_commitment) = (old_state.commitment, new_state.commitment);
+        let abandoned = Arc::new(AtomicBool::new(false));
+        let receiver = spawn_prover(stall, abandoned.clone(), move || {
+            prove_counter_transition(old_value, old_blinding, old_commitment, new_blinding, new_commitment)
+        });
+        let (transition, proof) = match receiver.recv_timeout(deadline) {
+            Ok(proved) => proved.ok_or(ExecutionError::ProofFailed)?,
+            Err(RecvTimeoutError::Timeout) => {
+                abandoned.store(true, Ordering::Relaxed);
+                return Err(ExecutionError::ProverTimeout { deadline_ms: deadline.as_millis() as u64 });
+            }
+            Err(RecvTimeoutError::Disconnected) => return Err(ExecutionError::ProofFailed),
+        };
+        // The proof only helps if it is anchored to the on-chain commitment,
+        // which also catches an old state that does not hold what it committed
+        if transition.old_commitment != snapshot.value_commitment {
+            return Err(ExecutionError::ProofFailed);
+        }
+        Ok((transition, proof))
+    }
+    
+    // Owner requests the result: parties send their shares to the client only,
+    // which checks every party's MAC before reconstructing, writing the value
+    // back as encrypted state and committing to it
+    pub fn reveal_mpc(&mut self, key: &[u8]) -> Result<u64, MpcError> {
+        let sharing = self.current.mpc_shares.as_ref().ok_or(MpcError::NotShared)?;
+        let value = self.reveal_shares(sharing)?;
+        Phase::Encrypt.span().in_scope(|| {
+            let state = self.seal(key, &value.to_le_bytes(), &self.padding);
+            self.current.store.write(COUNTER_SLOT, state);
+            self.current.recommit(value, &self.state_key(key));
+        });
+        Ok(value)
+    }
+    
+    fn reveal_shares(&self, sharing: &AuthSharing) -> Result<u64, MpcError> {
+        let network = MpcNetwork::new(self.mpc_network, &self.mpc_dealer).with_faults(&self.mpc_faults);
+        let (_, value, _) = Phase::Reconstruct.span().in_scope(|| {
+            network.run(
+                |ctx| ctx.reveal_to_client(&sharing.shares[ctx.id]),
+                |ctx| ctx.receive_revealed(&sharing.client_keys),
+            )
+        })?;
+        Ok(value?.value())
+    }
+    
+    fn decrypt_value(&self, state: &EncryptedState, key: &[u8]) -> Result<u64, ExecutionError> {
+        // Key holder's view: the client before proving, the owner before
+        // inputting the value to the MPC parties
+        decode_word(&state.open(&self.state_key(key)).ok_or(ExecutionError::DecryptFailed)?)
+    }
+}
+
+// Simulated contract host taking increments from many clients at once.
+// Clients execute optimistically against the snapshot they last read; the
+// host applies a transition only if it was computed from the current state
+// and passes validation. A stale transition is a conflict: the client
+// re-reads and retries.
+pub struct ContractHost {
+    counter: EncryptedCounter,
+    validator: TransitionValidator,
+    head: Mutex<CounterSnapshot>,
+    // Where committed heads are persisted, if anywhere
+    storage: Option<Mutex<Box<dyn StorageBackend + Send>>>,
+    // Events of committed transitions, in commit order
+    events: Mutex<Vec<EncryptedEvent>>,
+}
+
+// Retries per increment before a client gives up
+pub const HOST_MAX_ATTEMPTS: usize = 64;
+
+#[derive(Debug, Clone)]
+pub struct LoadReport {
+    pub model: ExecutionModel,
+    pub clients: usize,
+    pub committed: usize,
+    pub attempts: usize,
+    pub conflicts: usize,
+    pub rejected: usize,
+    pub abandoned: usize,
+    pub elapsed: Duration,
+}
+
+impl LoadReport {
+    // Share of submissions that hit a stale state
+    pub fn conflict_rate(&self) -> f64 {
+        self.conflicts as f64 / self.attempts.max(1) as f64
+    }
+    
+    // Extra executions per committed increment
+    pub fn retry_rate(&self) -> f64 {
+        (self.attempts - self.committed) as f64 / self.committed.max(1) as f64
+    }
+    
+    pub fn print(&self) {
+        println!(
+            "{:?}: {} clients, {} committed in {:.1}ms, {} attempts, conflict rate {:.1}%, {:.2} retries/commit, {} rejected, {} abandoned",
+            self.model,
+            self.clients,
+            self.committed,
+            self.elapsed.as_secs_f64() * 1_000.0,
+            self.attempts,
+            self.conflict_rate() * 100.0,
+            self.retry_rate(),
+            self.rejected,
+            self.abandoned
+        );
+    }
+}
+
+// Fault-injection mode: calls from one starting state, each forced to fail
+// with a fault drawn at the chosen rates. Nothing is applied, so every call
+// sees the same state and the only failures are the injected ones.
+pub struct FaultInjector {
+    rates: Vec<(InjectedFault, f64)>,
+    rng: StdRng,
+}
+
+// A call that failed, and what was injected into it
+#[derive(Debug, Clone)]
+pub struct FailedCall {
+    pub fault: Option<InjectedFault>,
+    pub error: ExecutionError,
+    pub latency_us: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct RobustnessReport {
+    pub model: ExecutionModel,
+    pub calls: usize,
+    pub injected: usize,
+    pub succeeded: usize,
+    pub success_us: u64, // total over the successful calls
+    pub failures: Vec<FailedCall>,
+}
+
+impl FaultInjector {
+    // Seeded, so every benchmark run draws the same faults
+    pub fn new(seed: u64) -> Self {
+        Self { rates: Vec::new(), rng: StdRng::seed_from_u64(seed) }
+    }
+    
+    pub fn with_rate(mut self, fault: InjectedFault, rate: f64) -> Self {
+        self.rates.retain(|(existing, _)| *existing != fault);
+        self.rates.push((fault, rate.clamp(0.0, 1.0)));
+        self
+    }
+    
+    // At most one fault per call, among those that apply to `model`
+    pub fn draw(&mut self, model: ExecutionModel) -> Option<InjectedFault> {
+        let roll: f64 = self.rng.gen();
+        let mut cumulative = 0.0;
+        self.rates.iter().filter(|(fault, _)| fault.applies_to(model)).find_map(|&(fault, rate)| {
+            cumulative += rate;
+            (roll < cumulative).then_some(fault)
+        })
+    }
+    
+    pub fn run(&mut self, counter: &EncryptedCounter, model: ExecutionModel, calls: usize, key: &[u8]) -> RobustnessReport {
+        let snapshot = counter.snapshot();
+        let mut report = RobustnessReport { model, calls, injected: 0, succeeded: 0, success_us: 0, failures: Vec::new() };
+        for _ in 0..calls {
+            let fault = self.draw(model);
+            report.injected += fault.is_some() as usize;
+            let (result, _) = counter.execute_with_fault(model, &snapshot, key, fault);
+            match result.error {
+                None => {
+                    report.succeeded += 1;
+                    report.success_us += result.total_time_us;
+                }
+                Some(error) => report.failures.push(FailedCall { fault, error, latency_us: result.total_time_us }),
+            }
+        }
+        report
+    }
+}
+
+impl RobustnessReport {
+    pub fn success_rate(&self) -> f64 {
+        self.succeeded as f64 / self.calls.max(1) as f64
+    }
+    
+    pub fn mean_success_us(&self) -> Option<u64> {
+        (self.succeeded > 0).then(|| self.success_us / self.succeeded as u64)
+    }
+    
+    // How often `fault` failed a call, and the mean time until it did
+    pub fn failure_path(&self, fault: InjectedFault) -> Option<(usize, u64)> {
+        let latencies: Vec<u64> = self.failures
+            .iter()
+            .filter(|failed| failed.fault == Some(fault))
+            .map(|failed| failed.latency_us)
+            .collect();
+        (!latencies.is_empty()).then(|| (latencies.len(), latencies.iter().sum::<u64>() / latencies.len() as u64))
+    }
+    
+    // Every failure was injected, and failed with the error its fault causes
+    pub fn failures_explained(&self) -> bool {
+        self.failures.iter().all(|failed| failed.fault.is_some_and(|fault| fault.explains(&failed.error)))
+    }
+    
+    pub fn print(&self) {
+        println!(
+            "{:?}: {} calls, {} faults injected, {:.1}% succeeded (mean {}μs)",
+            self.model,
+            self.calls,
+            self.injected,
+            self.success_rate() * 100.0,
+            self.mean_success_us().map_or("-".to_string(), |us| us.to_string())
+        );
+        for fault in InjectedFault::ALL {
+            if let Some((count, mean_us)) = self.failure_path(fault) {
+                println!("  {:?}: {} failed, {}μs to fail", fault, count, mean_us);
+            }
+        }
+    }
+}
+
+impl ContractHost {
+    // Takes over the counter's deployment (keys, enclave, committee) and its
+    // current state as the chain head
+    pub fn new(counter: EncryptedCounter) -> Self {
+        Self {
+            validator: counter.validator(),
+            head: Mutex::new(counter.snapshot()),
+            storage: None,
+            events: Mutex::new(counter.events().to_vec()),
+            counter,
+        }
+    }
+    
+    // Persist every committed head to `backend`, starting with the current one
+    pub fn with_storage(mut self, mut backend: Box<dyn StorageBackend + Send>) -> io::Result<Self> {
+        self.head().store.persist(COUNTER_CONTRACT_ID, backend.as_mut())?;
+        self.storage = Some(Mutex::new(backend));
+        Ok(self)
+    }
+    
+    pub fn head(&self) -> CounterSnapshot {
+        self.head.lock().expect("host head poisoned").clone()
+    }
+    
+    // Applies `next` only if `previous` is still the head and the evidence holds
+    pub fn submit(
+        &self,
+        previous: &StateCommitment,
+        next: CounterSnapshot,
+        evidence: &ExecutionEvidence,
+    ) -> Result<StateCommitment, Rejection> {
+        let mut head = self.head.lock().expect("host head poisoned");
+        if head.commitment() != *previous {
+            return Err(Rejection::StalePrevious);
+        }
+        let accepted = self.validator.validate(previous, next.state(), evidence)?;
+        // Durable before it becomes the head
+        if let Some(storage) = &self.storage {
+            let mut backend = storage.lock().expect("host storage poisoned");
+            next.store.persist(COUNTER_CONTRACT_ID, backend.as_mut()).map_err(|_| Rejection::StorageFailed)?;
+        }
+        self.events.lock().expect("host events poisoned").extend(next.events.iter().cloned());
+        *head = next;
+        Ok(accepted)
+    }
+    
+    // Public log, as an indexer sees it: topics in the clear, payloads sealed
+    pub fn events(&self) -> Vec<EncryptedEvent> {
+        self.events.lock().expect("host events poisoned").clone()
+    }
+    
+    // `clients` threads each commit `increments` increments, retrying on conflict
+    pub fn run_load(&self, model: ExecutionModel, clients: usize, increments: usize, key: &[u8]) -> LoadReport {
+        let start = Instant::now();
+        let per_client: Vec<[usize; 5]> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..clients)
+                .map(|_| scope.spawn(|| self.client_loop(model, increments, key)))
+                .collect();
+            handles.into_iter().map(|h| h.join().expect("client thread panicked")).collect()
+        });
+        let total = |i: usize| per_client.iter().map(|counts| counts[i]).sum();
+        LoadReport {
+            model,
+            clients,
+            committed: total(0),
+            attempts: total(1),
+            conflicts: total(2),
+            rejected: total(3),
+            abandoned: total(4),
+            elapsed: start.elapsed(),
+        }
+    }
+    
+    // [committed, attempts, conflicts, rejected, abandoned]
+    fn client_loop(&self, model: ExecutionModel, increments: usize, key: &[u8]) -> [usize; 5] {
+        let mut counts = [0usize; 5];
+        for _ in 0..increments {
+            let mut done = false;
+            for _ in 0..HOST_MAX_ATTEMPTS {
+                counts[1] += 1;
+                let snapshot = self.head();
+                let (_, next) = self.counter.execute(model, &snapshot, key);
+                let Ok((next, evidence)) = next else {
+                    counts[3] += 1;
+                    break;
+                };
+                match self.submit(&snapshot.commitment(), next, &evidence) {
+                    Ok(_) => {
+                        counts[0] += 1;
+                        done = true;
+                        break;
+                    }
+                    Err(Rejection::StalePrevious) => counts[2] += 1,
+                    Err(_) => {
+                        counts[3] += 1;
+                        break;
+                    }
+                }
+            }
+            if !done {
+                counts[4] += 1;
+            }
+        }
+        counts
+    }
+    
+    // Owner's view of the head: decrypt, or reconstruct if the value is shared
+    pub fn reveal(&self, key: &[u8]) -> Option<u64> {
+        let head = self.head();
+        match (&head.mpc_shares, &head.fhe_value) {
+            (Some(sharing), _) => self.counter.reveal_shares(sharing).ok(),
+            (None, Some(ciphertext)) => Some(LweSecretKey::derive(key).decrypt(ciphertext)),
+            (None, None) => self.counter.decrypt_value(head.state(), key).ok(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ExecutionResult {
+    pub model: ExecutionModel,
+    pub success: bool,
+    pub total_time_us: u64,
+    pub breakdown: ExecutionBreakdown,
+    pub proof_size_bytes: usize,
+    pub slots_touched: usize,                 // storage slots read or written
+    pub network_stats: Option<NetworkStats>,  // MPC communication cost
+    pub lwe_stats: Option<LweStats>,          // FHE ciphertext size and noise
+    pub hybrid_latency: Option<HybridLatency>, // hybrid: accepted vs provable
+    pub gas_used: u64,
+    pub gas_limit: u64,                       // 0 if the run was not metered
+    pub out_of_gas: bool,
+    pub event_commitments: Vec<[u8; 32]>,     // one per event emitted
+    pub error: Option<ExecutionError>,        // why the run failed, if it did
+}
+
+// The setup span covers the whole run, so only entering and leaving it fall
+// outside the breakdown, plus truncation of each phase to whole μs. The
+// same tolerance holds in every build profile: 2% of the total, at least
+// 100μs (first runs of a code path can page-fault on the way out).
+const PHASE_TOLERANCE_PERCENT: u64 = 2;
+const PHASE_TOLERANCE_MIN_US: u64 = 100;
+
+impl ExecutionResult {
+    // Network wait happens on the party threads; the critical path's share
+    // is moved out of the compute span that covered it
+    pub fn from_phases(
+        model: ExecutionModel,
+        success: bool,
+        mut timings: PhaseTimings,
+        proof_size_bytes: usize,
+        slots_touched: usize,
+        network_stats: Option<NetworkStats>,
+    ) -> Self {
+        if let Some(stats) = &network_stats {
+            timings.reattribute(Phase::Compute, Phase::Network, Duration::from_micros(stats.max_network_wait_us()));
+        }
+        Self {
+            model,
+            success,
+            total_time_us: timings.total.as_micros() as u64,
+            breakdown: timings.breakdown(),
+            proof_size_bytes,
+            slots_touched,
+            network_stats,
+            lwe_stats: None,
+            hybrid_latency: None,
+            gas_used: 0,
+            gas_limit: 0,
+            out_of_gas: false,
+            event_commitments: Vec::new(),
+            error: None,
+        }
+    }
+    
+    pub fn with_error(mut self, error: Option<&ExecutionError>) -> Self {
+        if let Some(error) = error {
+            self.success = false;
+            self.error = Some(error.clone());
+        }
+        self
+    }
+    
+    pub fn with_gas(mut self, gas: &GasMeter) -> Self {
+        self.gas_used = gas.used();
+        self.gas_limit = gas.limit();
+        self.out_of_gas = gas.is_out_of_gas();
+        self
+    }
+    
+    // How long this model takes per unit of gas charged
+    pub fn ns_per_gas(&self) -> Option<f64> {
+        (self.gas_used > 0).then(|| self.total_time_us as f64 * 1_000.0 / self.gas_used as f64)
+    }
+    
+    pub fn unattributed_us(&self) -> u64 {
+        self.total_time_us.saturating_sub(self.breakdown.sum_us())
+    }
+    
+    // Phases are exclusive and nested in the total, so they can only fall
+    // short of it
+    pub fn phases_sum_to_total(&self) -> bool {
+        let tolerance = (self.total_time_us * PHASE_TOLERANCE_PERCENT / 100).max(PHASE_TOLERANCE_MIN_US);
+        self.breakdown.sum_us() <= self.total_time_us && self.unattributed_us() <= tolerance
+    }
+    
+    pub fn overhead_vs_plaintext(&self, baseline: &PlaintextBaseline) -> f64 {
+        self.total_time_us as f64 * 1_000.0 / baseline.median_ns
+    }
+    
+    pub fn meets_target(&self, baseline: &PlaintextBaseline, max_overhead: f64) -> bool {
+        self.overhead_vs_plaintext(baseline) < max_overhead
+    }
+    
+    pub fn print_report(&self, baseline: &PlaintextBaseline) {
+        println!("\n=== Execution Model: {:?} ===", self.model);
+        println!("Success: {}", self.success);
+        if let Some(error) = &self.error {
+            println!("Error: {}", error);
+        }
+        println!("Total time: {}μs", self.total_time_us);
+        println!("Slots touched: {}", self.slots_touched);
+        if self.gas_limit > 0 {
+            println!("Gas: {} of {}{}", self.gas_used, self.gas_limit, if self.out_of_gas { " (out of gas)" } else { "" });
+        }
+        if !self.event_commitments.is_empty() {
+            println!("Events emitted: {}", self.event_commitments.len());
+        }
+        println!("\nBreakdown:");
+        for (phase, us) in self.breakdown.phases() {
+            if us > 0 {
+                println!("  {}: {}μs", phase.name(), us);
+            }
+        }
+        println!("  (unattributed): {}μs", self.unattributed_us());
+        println!("Phases sum to total: {}", self.phases_sum_to_total());
+        
+        if self.network_stats.is_some() {
+            println!("\nMPC phases:");
+            println!("  Preprocessing (offline): {}μs", self.breakdown.preprocess_us);
+            println!("  Online: {}μs", self.breakdown.online_us());
+        }
+        
+        if let Some(stats) = &self.network_stats {
+            println!("\nCommunication:");
+            println!("  Rounds: {}", stats.rounds);
+            for node in &stats.nodes {
+                let label = if node.node + 1 == stats.nodes.len() { "Client".to_string() } else { format!("Party {}", node.node) };
+                if node.dropped_out {
+                    println!("  {}: dropped out", label);
+                } else {
+                    println!("  {}: {} msgs, {} bytes", label, node.messages_sent, node.bytes_sent);
+                }
+            }
+            println!("  Total: {} bytes", stats.total_bytes());
+            if stats.detection_us() > 0 {
+                println!("  Dropout detected after: {}μs", stats.detection_us());
+            }
+        }
+        
+        if let Some(stats) = &self.lwe_stats {
+            println!("\nHomomorphic ciphertext:");
+            println!(
+                "  Size: {} bytes ({:.0}× the {}-byte plaintext)",
+                stats.ciphertext_bytes,
+                stats.ciphertext_bytes as f64 / stats.plaintext_bytes as f64,
+                stats.plaintext_bytes
+            );
+            println!("  Growth this call: {} bytes", stats.growth_bytes);
+            println!("  Noise budget left: {:.1} bits", stats.noise_budget_bits);
+        }
+        
+        if let Some(latency) = &self.hybrid_latency {
+            println!("\nHybrid latency:");
+            println!("  Optimistic (enclave signature): {}μs", latency.optimistic_us);
+            match latency.proof_us {
+                Some(proof_us) => println!("  Proof ready after: +{}μs", proof_us),
+                None => println!("  Proof: prover missed its deadline; a challenge reverts the call"),
+            }
+        }
+        
+        if self.proof_size_bytes > 0 {
+            println!("\nEvidence size: {:.1}KB", self.proof_size_bytes as f64 / 1024.0);
+        }
+        
+        let overhead = self.overhead_vs_plaintext(baseline);
+        println!("\nOverhead vs plaintext {}: {:.1}×", baseline.contract, overhead);
+        println!("Meets <{}× target: {}", OVERHEAD_TARGET, self.meets_target(baseline, OVERHEAD_TARGET));
+        println!("================================\n");
+    }
+}
+
+// Benchmark all execution models
+pub fn run_execution_benchmark() {
+    println!("Starting Encrypted Execution Benchmark\n");
+    
+    let key = b"test_encryption_key_32_bytes!!!";
+    let bids = [120, 340, 275, 90];
+    let mut auction = SealedBidAuction::new(3, 5);
+    for bid in bids {
+        if let Err(error) = auction.submit_bid(bid) {
+            println!("Bid {} rejected: {}", bid, error);
+        }
+    }
+    
+    // Each contract's logic run unencrypted; overheads are against these
+    let plain_counter = EncryptedCounter::new(0, key);
+    let counter_baseline = PlaintextBaseline::measure("counter", || {
+        plain_counter.plaintext_call(std::hint::black_box(41))
+    });
+    let ledger_baseline = PlaintextBaseline::measure("ledger", || {
+        let mut balances = std::hint::black_box([100, 50]);
+        MpcTokenLedger::plaintext_transfer(&mut balances, 0, 1, 30);
+        balances
+    });
+    let auction_baseline = PlaintextBaseline::measure("auction", || std::hint::black_box(&auction).plaintext_outcome());
+    counter_baseline.print();
+    ledger_baseline.print();
+    auction_baseline.print();
+    println!();
+    
+    let mut results = Vec::new();
+    
+    for model in ExecutionModel::ALL {
+        let mut counter = EncryptedCounter::new(0, key);
+        match counter.increment(model, key) {
+            Ok(result) => {
+                result.print_report(&counter_baseline);
+                results.push(result);
+            }
+            Err(error) => {
+                println!("\n=== Execution Model: {:?} ===", model);
+                println!("Success: false");
+                println!("Error: {}", error);
+            }
+        }
+    }
+    
+    // MPC correctness: increments and transfers ran on shares only
+    println!("=== MPC Share-Local Arithmetic ===");
+    let mut counter = EncryptedCounter::new(41, key);
+    for _ in 0..2 {
+        if let Err(error) = counter.increment(ExecutionModel::MPCBased, key) {
+            println!("Increment failed: {}", error);
+        }
+    }
+    let (revealed, reveal_timings) = PhaseTimings::record(|| counter.reveal_mpc(key));
+    println!("Counter 41 + 2 increments, revealed by owner: {:?}", revealed);
+    println!(
+        "Reveal: reconstruct {}μs, re-encrypt {}μs",
+        reveal_timings.get(Phase::Reconstruct).as_micros(),
+        reveal_timings.get(Phase::Encrypt).as_micros()
+    );
+    
+    let mut ledger = MpcTokenLedger::new(&[100, 50], 3, 5);
+    let amount = ledger.share_amount(30);
+    ledger.transfer(0, 1, &amount);
+    let balances = (ledger.reveal_balance(0), ledger.reveal_balance(1));
+    println!("Transfer 30 from [100, 50]: {:?}", balances);
+    
+    // FHE correctness and noise: additions only, never a decryption
+    println!("\n=== Homomorphic Arithmetic (LWE) ===");
+    let lwe_key = LweSecretKey::derive(key);
+    let mut counter = EncryptedCounter::new(41, key);
+    for _ in 0..2 {
+        if let Err(error) = counter.increment(ExecutionModel::FHEBased, key) {
+            println!("Increment failed: {}", error);
+        }
+    }
+    let revealed = counter.reveal_fhe(key);
+    println!("Counter 41 + 2 increments, decrypted by owner: {:?}", revealed);
+    
+    let mut ledger = HomomorphicLedger::new(&[100, 50], &lwe_key);
+    ledger.transfer(0, 1, &lwe_key.encrypt(30));
+    let balances = (ledger.balance(0).map(|b| lwe_key.decrypt(b)), ledger.balance(1).map(|b| lwe_key.decrypt(b)));
+    println!("Transfer 30 from [100, 50]: {:?}", balances);
+    
+    // Noise grows with every ciphertext addition; the tracked bound must cover it
+    let additions = 1_000;
+    let one = lwe_key.encrypt(1);
+    let start = Instant::now();
+    let sum = (1..additions).fold(one.clone(), |sum, _| sum.add(&one));
+    let add_time = start.elapsed();
+    println!(
+        "{} additions: {:.2}μs each, noise {} (bound {}), budget {:.1} → {:.1} bits",
+        additions,
+        add_time.as_secs_f64() * 1e6 / additions as f64,
+        lwe_key.noise(&sum),
+        sum.noise_bound(),
+        one.noise_budget_bits(),
+        sum.noise_budget_bits()
+    );
+    
+    // Same bytecode under every model, no harness changes
+    println!("\n=== Bytecode VM ===");
+    let scaled_add = Program::new(vec![
+        Instruction::Load(COUNTER_SLOT),
+        Instruction::Arg(0),
+        Instruction::Push(2),
+        Instruction::Mul,
+        Instruction::Add,
+        Instruction::Store(COUNTER_SLOT),
+        Instruction::Halt,
+    ]);
+    println!("Program: slot 0 += 2 · arg 0 ({} bytes), called with 3 on 41", scaled_add.encode().len());
+    for model in ExecutionModel::ALL {
+        let mut counter = EncryptedCounter::new(41, key).with_program(scaled_add.clone(), &[3]);
+        let result = counter.increment(model, key);
+        let value = ContractHost::new(counter).reveal(key);
+        match &result {
+            Ok(result) => println!("{:?}: success, {}μs, value {:?}", model, result.total_time_us, value),
+            Err(error) => println!("{:?}: {}, value {:?}", model, error, value),
+        }
+    }
+    
+    // Counter as a WASM module, state through host functions, fuel-metered
+    println!("\n=== WASM Contract Host ===");
+    match WasmHost::new() {
+        Ok(host) => {
+            let load_start = Instant::now();
+            let counter = host.load(COUNTER_WASM);
+            let load_us = load_start.elapsed().as_micros();
+            let mut store = EncryptedStore::new();
+            store.write(COUNTER_SLOT, EncryptedCounter::new(41, key).state().clone());
+            let call_start = Instant::now();
+            let run = counter.and_then(|counter| host.call(&counter, &store, &EncryptedCounter::viewing_key(key), 2, WASM_DEFAULT_FUEL));
+            let call_us = call_start.elapsed().as_micros();
+            match run {
+                Ok(run) => println!(
+                    "Module compiled in {}μs, call in {}μs, fuel used {} (limit {})",
+                    load_us, call_us, run.fuel_used, WASM_DEFAULT_FUEL
+                ),
+                Err(error) => println!("Counter module failed: {}", error),
+            }
+        }
+        Err(error) => println!("Wasmtime engine unavailable: {}", error),
+    }
+    
+    // One gas schedule, four cost profiles: time per gas shows the mispricing
+    println!("\n=== Gas Accounting ===");
+    let schedule = GasSchedule::default();
+    println!("Schedule: {:?}, limit {}", schedule, DEFAULT_GAS_LIMIT);
+    let mut ns_per_gas = Vec::new();
+    for model in ExecutionModel::ALL {
+        match EncryptedCounter::new(41, key).increment(model, key) {
+            Ok(result) => {
+                println!(
+                    "{:?}: {} gas, {}μs, {:.1}ns per gas",
+                    model,
+                    result.gas_used,
+                    result.total_time_us,
+                    result.ns_per_gas().unwrap_or(0.0)
+                );
+                ns_per_gas.extend(result.ns_per_gas());
+            }
+            Err(error) => println!("{:?}: {}", model, error),
+        }
+    }
+    let spread = ns_per_gas.iter().cloned().fold(0.0, f64::max) / ns_per_gas.iter().cloned().fold(f64::INFINITY, f64::min);
+    println!("Time per gas varies {:.1}× across models", spread);
+    
+    // Failures surface as typed errors and leave the state as it was
+    println!("\n=== Execution Errors ===");
+    for model in ExecutionModel::ALL {
+        if let Err(error) = EncryptedCounter::new(41, key).increment(model, b"wrong key") {
+            println!("{:?}, wrong key: {}", model, error);
+        }
+    }
+    
+    // Parties that lost their shares cannot run the next increment
+    let mut counter = EncryptedCounter::new(41, key);
+    if let Err(error) = counter.increment(ExecutionModel::MPCBased, key) {
+        println!("MPCBased: {}", error);
+    }
+    let mut snapshot = counter.snapshot();
+    if let Some(sharing) = &mut snapshot.mpc_shares {
+        sharing.shares.truncate(2);
+    }
+    let (result, _) = counter.execute(ExecutionModel::MPCBased, &snapshot, key);
+    if let Some(error) = &result.error {
+        println!("MPCBased, 3 of 5 shares lost: {}", error);
+    }
+    
+    // Same calls with faults forced at fixed rates: how often each model
+    // survives, and how long it takes to find out when it does not
+    println!("\n=== Fault Injection ===");
+    let (fault_rate, fault_calls) = (0.1, 40);
+    println!("Each applicable fault at {:.0}% of calls, {} calls per model", fault_rate * 100.0, fault_calls);
+    let mut injector = InjectedFault::ALL
+        .into_iter()
+        .fold(FaultInjector::new(48), |injector, fault| injector.with_rate(fault, fault_rate));
+    for model in ExecutionModel::ALL {
+        let mut counter = EncryptedCounter::new(41, key);
+        if let Err(error) = counter.prepare(model, key) {
+            println!("{:?}: {}", model, error);
+        }
+        injector.run(&counter, model, fault_calls, key).print();
+    }
+    
+    // Events from two contracts in one public log; each key reads its own
+    println!("\n=== Encrypted Events ===");
+    let mut counter = EncryptedCounter::new(41, key);
+    for model in [ExecutionModel::ClientSideZK, ExecutionModel::TEEBased, ExecutionModel::MPCBased] {
+        if let Err(error) = counter.increment(model, key) {
+            println!("{:?}: {}", model, error);
+        }
+    }
+    let counter_topic = topic_hash(CounterCalled::TOPIC);
+    if let Some(event) = counter.events().first() {
+        println!(
+            "{} events in the log, {} bytes each (counter), topic {:02x?}…",
+            counter.events().len(),
+            event.ciphertext.len(),
+            &counter_topic[..4]
+        );
+    }
+    let calls = scan_events::<CounterCalled>(counter.events(), &EncryptedCounter::viewing_key(key));
+    println!("Counter key reads {} calls", calls.len());
+    
+    // Secret-dependent timing on the paths that touch plaintext or key material
+    println!("\n=== Timing Leakage (dudect) ===");
+    let harness = TimingHarness::new(20_000).with_batch(4);
+    let secret_value = |class: usize| if class == 0 { 0 } else { rand::thread_rng().gen::<u64>() };
+    let leak_counter = EncryptedCounter::new(0, key);
+    let (kem_dk, kem_ek) = MlKem512::generate(&mut rand::thread_rng());
+    let reports = [
+        harness.test(
+            "AEAD seal, value 0 vs random",
+            |class| secret_value(class).to_le_bytes(),
+            |plaintext| EncryptedState::new(plaintext, key),
+        ),
+        harness.test(
+            "AEAD open, value 0 vs random",
+            |class| EncryptedState::new(&secret_value(class).to_le_bytes(), key),
+            |state| state.decrypt(key),
+        ),
+        harness.test(
+            "Counter decrypt, value 0 vs random",
+            |class| leak_counter.seal(key, &secret_value(class).to_le_bytes(), &PaddingPolicy::None),
+            |state| leak_counter.decrypt_value(state, key),
+        ),
+        harness.test(
+            "ML-KEM decapsulation, valid vs corrupted ciphertext",
+            |class| {
+                let (mut ciphertext, _) = kem_ek.encapsulate(&mut rand::thread_rng()).expect("ML-KEM encapsulation");
+                if class == 1 {
+                    let last = ciphertext.len() - 1;
+                    ciphertext[last] ^= 1;
+                }
+                ciphertext
+            },
+            |ciphertext| kem_dk.decapsulate(ciphertext).ok(),
+        ),
+        harness.test(
+            "Shamir reconstruction (3-of-5), value 0 vs random",
+            |class| SharedValue::share(secret_value(class), 3, 5),
+            |sharing| sharing.reconstruct(),
+        ),
+    ];
+    // Length is not hidden: seal time (and size) track the plaintext length
+    let by_length = harness.test(
+        "AEAD seal, 8 vs 4096-byte plaintext",
+        |class| vec![0u8; if class == 0 { 8 } else { 4096 }],
+        |plaintext| EncryptedState::new(plaintext, key),
+    );
+    // Control: an early-exit comparison must be caught
+    let control = harness.test(
+        "Control: early-exit compare, mismatch at byte 0 vs none",
+        |class| {
+            let mut guess = vec![0u8; 4096];
+            guess[0] = (class == 0) as u8;
+            guess
+        },
+        |guess| guess.as_slice() == [0u8; 4096].as_slice(),
+    );
+    for report in reports.iter().chain([&by_length, &control]) {
+        report.print();
+    }
+    
+    // What each padding policy costs, and what it hides, on sample states
+    println!("\n=== State Padding ===");
+    let samples: Vec<(&str, Vec<u8>)> = vec![
+        ("counter", 41u64.to_le_bytes().to_vec()),
+        ("token ledger, 5 balances", [100u64, 50, 0, 0, 0].iter().flat_map(|b| b.to_le_bytes()).collect()),
+        ("auction, 4 bids", bids.iter().flat_map(|b: &u64| b.to_le_bytes()).collect()),
+        ("KV store head, 1 slot", EncryptedCounter::new(41, key).snapshot().store.head().to_bytes()),
+        ("WASM memory, 1 page", vec![0u8; 65536]),
+    ];
+    let policies = [PaddingPolicy::None, PaddingPolicy::PowerOfTwo, PaddingPolicy::fixed()];
+    let unpadded: usize = samples.iter().map(|(_, plaintext)| plaintext.len() + AEAD_OVERHEAD).sum();
+    for policy in &policies {
+        let sizes: Vec<usize> = samples
+            .iter()
+            .map(|(_, plaintext)| EncryptedState::padded(plaintext, key, policy).ciphertext.len())
+            .collect();
+        let total: usize = sizes.iter().sum();
+        let growth = sizes
+            .iter()
+            .zip(&samples)
+            .map(|(&size, (_, plaintext))| size as f64 / (plaintext.len() + AEAD_OVERHEAD) as f64)
+            .sum::<f64>()
+            / sizes.len() as f64;
+        println!(
+            "{:?}: {} bytes sealed, +{:.1}% over unpadded, {:.2}× per state on average, sizes {:?}",
+            policy,
+            total,
+            (total as f64 / unpadded as f64 - 1.0) * 100.0,
+            growth,
+            sizes
+        );
+    }
+    
+    let bucket = PaddingPolicy::fixed();
+    let in_bucket = harness.test(
+        "AEAD open, 8 vs 200-byte plaintext in one 256B bucket",
+        |class| EncryptedState::padded(&vec![1u8; if class == 0 { 8 } else { 200 }], key, &bucket),
+        |state| state.decrypt(key),
+    );
+    in_bucket.print();
+    
+    // Non-linear MPC: sealed-bid auction built on Beaver-triple comparisons
+    println!("\n=== Sealed-Bid Auction (MPC) ===");
+    println!("Bidders: {}, Beaver triples: {}", bids.len(), auction.triples_needed());
+    let (outcome, auction_result) = auction.run_mpc();
+    auction_result.print_report(&auction_baseline);
+    println!("Outcome: {:?}", outcome);
+    
+    // TEE attestation: clients refuse enclaves with the wrong code or platform
+    println!("\n=== TEE Attestation ===");
+    let platform = Arc::new(SimulatedPlatform::new());
+    let expected = measure(COUNTER_CONTRACT_CODE);
+    let nonce = [7u8; 32];
+    let genuine = Enclave::launch(platform.clone(), COUNTER_CONTRACT_CODE);
+    let report = genuine.attest(nonce);
+    println!("Report size: {} bytes", report.size_bytes());
+    let pinned = platform.verifying_key();
+    
+    // Verifier sees only the on-chain commitment, the new one and the proof
+    println!("\n=== ZK State Transition (STARK) ===");
+    let mut zk_counter = EncryptedCounter::new(41, key);
+    if let Err(error) = zk_counter.increment(ExecutionModel::ClientSideZK, key) {
+        println!("Increment failed: {}", error);
+    }
+    let proof_len = match zk_counter.evidence() {
+        Some(ExecutionEvidence::ZkProof { proof, .. }) => proof.len(),
+        _ => 0,
+    };
+    println!(
+        "Proof size: {} bytes, trace {} chain + {} masking rows",
+        proof_len,
+        COMMITMENT_TRACE_LENGTH,
+        COUNTER_TRACE_LENGTH - COMMITMENT_TRACE_LENGTH
+    );
+    
+    // What a node pays to check each model's evidence, next to the prover
+    println!("\n=== Transition Validation ===");
+    for model in ExecutionModel::ALL {
+        let mut counter = EncryptedCounter::new(41, key);
+        let prepared = counter.prepare(model, key);
+        let validator = counter.validator();
+        let previous = counter.state_commitment();
+        let result = match prepared.and_then(|_| counter.increment(model, key)) {
+            Ok(result) => result,
+            Err(error) => {
+                println!("{:?}: {}", model, error);
+                continue;
+            }
+        };
+        let Some(evidence) = counter.evidence().cloned() else {
+            continue;
+        };
+        let new_state = counter.state().clone();
+        
+        let (verdict, verify_timings) = PhaseTimings::record(|| validator.validate(&previous, &new_state, &evidence));
+        println!(
+            "{:?}: prover {}μs, verifier {}μs, evidence {:.1}KB",
+            model,
+            result.total_time_us,
+            verify_timings.get(Phase::Verify).as_micros(),
+            evidence.size_bytes() as f64 / 1024.0
+        );
+        if let Err(rejection) = verdict {
+            println!("{:?}: rejected: {}", model, rejection);
+        }
+    }
+    
+    // Hybrid: usable on the enclave's signature, its commitments proven if
+    // challenged
+    println!("\n=== Hybrid TEE + ZK ===");
+    let mut counter = EncryptedCounter::new(41, key);
+    let validator = counter.validator();
+    for _ in 0..3 {
+        let previous = counter.state_commitment();
+        let result = match counter.increment(ExecutionModel::HybridTEEZK, key) {
+            Ok(result) => result,
+            Err(error) => {
+                println!("Increment failed: {}", error);
+                break;
+            }
+        };
+        let (Some(latency), Some(evidence)) = (result.hybrid_latency, counter.evidence().cloned()) else {
+            break;
+        };
+        let new_state = counter.state().clone();
+        let (accepted, accept_timings) = PhaseTimings::record(|| validator.validate(&previous, &new_state, &evidence));
+        let (upheld, challenge_timings) = PhaseTimings::record(|| validator.challenge(&evidence));
+        let verify_us = challenge_timings.get(Phase::Verify).as_micros() as u64;
+        let proof = latency.proof_us.map_or("missed its deadline".to_string(), |us| format!("+{}μs", us));
+        println!(
+            "Optimistic {}μs (+{}μs to accept), proof {}, challenge check {}μs",
+            latency.optimistic_us,
+            accept_timings.get(Phase::Verify).as_micros(),
+            proof,
+            verify_us
+        );
+        if let Some(challenge_us) = latency.challenge_us(HYBRID_CHALLENGE_WINDOW, verify_us) {
+            println!(
+                "  Worst-case challenge latency: {:.3}s (simulated {}s window + proof + verify)",
+                challenge_us as f64 / 1e6,
+                HYBRID_CHALLENGE_WINDOW.as_secs()
+            );
+        }
+        if let Err(rejection) = accepted.map(|_| ()).and(upheld) {
+            println!("Rejected: {}", rejection);
+        }
+    }
+    
+    // Selective disclosure: a scoped key opens its subtree, siblings stay sealed
+    println!("\n=== Viewing Keys (HDVK) ===");
+    let master = ViewingKey::master(key);
+    let mut states = Vec::new();
+    for contract in ["counter", "ledger"] {
+        for epoch in 0..2 {
+            for slot in 0..2 {
+                let path = KeyPath::contract(contract).epoch(epoch).slot(slot);
+                let plaintext = format!("{}/{}/{}", contract, epoch, slot);
+                if let Some(state) = EncryptedState::at(path.clone(), plaintext.as_bytes(), &master) {
+                    states.push((path, plaintext, state));
+                }
+            }
+        }
+    }
+    let scope = KeyPath::contract("counter").epoch(1);
+    let exported = master.export_scoped(&scope).unwrap_or_default();
+    println!("Exported key for {:?}: {} bytes", scope.segments(), exported.len());
+    let auditor = ViewingKey::import(&exported);
+    let opens = |key: &Option<ViewingKey>, state: &EncryptedState| {
+        key.as_ref().and_then(|key| state.open(key))
+    };
+    let readable = states.iter().filter(|(_, _, state)| opens(&auditor, state).is_some()).count();
+    println!("It opens {} of {} states", readable, states.len());
+    
+    // Rotation: history stays readable with old keys, new writes do not
+    println!("\n=== Key Rotation ===");
+    let ledger_states: Vec<EncryptedState> = states.iter()
+        .filter(|(path, _, _)| KeyPath::contract("ledger").epoch(0).contains(path))
+        .map(|(_, _, state)| state.clone())
+        .collect();
+    let mut rotation_enclave = Enclave::launch(platform.clone(), COUNTER_CONTRACT_CODE);
+    let provision_report = rotation_enclave.attest(nonce);
+    let contract_key = master.export_scoped(&KeyPath::contract("ledger")).unwrap_or_default();
+    rotation_enclave.provision_key("ledger", &encrypt_input_for_enclave(&provision_report, &contract_key));
+    
+    let (owner_dk, owner_ek) = MlKem512::generate(&mut rand::thread_rng());
+    let rotate_start = Instant::now();
+    let rotation = rotation_enclave.rotate_keys("ledger", &ledger_states, 1, &owner_ek);
+    let rotate_time = rotate_start.elapsed();
+    match &rotation {
+        Some(rotation) => {
+            println!("Rotated {} states to epoch {} in {}μs", rotation.states.len(), rotation.to_epoch, rotate_time.as_micros());
+            println!(
+                "Attestation verifies: {}, owner opens the epoch key: {}",
+                rotation.verify(&ledger_states, &pinned, &expected),
+                rotation.open_epoch_key(&owner_dk).is_some()
+            );
+        }
+        None => println!("Enclave did not rotate the ledger states"),
+    }
+    
+    // Multi-slot contract storage under one Merkle root
+    println!("\n=== Encrypted KV Store ===");
+    let mut store = EncryptedStore::new();
+    let slot_state = |slot: u64, value: u64| {
+        EncryptedState::at(KeyPath::contract("kv").epoch(0).slot(slot), &value.to_le_bytes(), &master)
+    };
+    let initial: Vec<(u64, EncryptedState)> = [0u64, 1, 7, 1 << 40]
+        .into_iter()
+        .filter_map(|slot| Some((slot, slot_state(slot, slot * 10)?)))
+        .collect();
+    let write_start = Instant::now();
+    for (slot, state) in initial {
+        store.write(slot, state);
+    }
+    let write_time = write_start.elapsed();
+    println!(
+        "{} slots, root {:02x?}…, {}μs per write, proof {} bytes",
+        store.len(),
+        &store.root()[..4],
+        write_time.as_micros() / store.len().max(1) as u128,
+        store.prove(0).size_bytes()
+    );
+    
+    // Many clients incrementing one contract: stale transitions must be
+    // rejected, and every increment must land exactly once
+    println!("\n=== Concurrent Clients ===");
+    let (clients, increments) = (4, 3);
+    for model in ExecutionModel::ALL {
+        let mut counter = EncryptedCounter::new(0, key);
+        if let Err(error) = counter.prepare(model, key) {
+            println!("{:?}: {}", model, error);
+        }
+        let host = ContractHost::new(counter);
+        let report = host.run_load(model, clients, increments, key);
+        report.print();
+        println!("{:?}: final value {:?} after {} increments", model, host.reveal(key), report.committed);
+    }
+    
+    // Committed heads on disk survive the host going away
+    println!("\n=== Persistent Storage ===");
+    let storage_dir = std::env::temp_dir().join(format!("ecliptica-store-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&storage_dir);
+    let mut memory = MemoryBackend::default();
+    let memory_start = Instant::now();
+    match store.persist("kv", &mut memory) {
+        Ok(()) => println!("KV store persisted to memory in {}μs", memory_start.elapsed().as_micros()),
+        Err(error) => println!("KV store not persisted to memory: {}", error),
+    }
+    
+    let mut counter = EncryptedCounter::new(0, key);
+    counter.prepare_enclave(key);
+    let persisted = FileBackend::open(&storage_dir).and_then(|backend| ContractHost::new(counter).with_storage(Box::new(backend)));
+    match persisted {
+        Ok(host) => {
+            let report = host.run_load(ExecutionModel::TEEBased, 2, 3, key);
+            println!(
+                "{} commits persisted in {:.1}ms ({:.2}ms per commit)",
+                report.committed,
+                report.elapsed.as_secs_f64() * 1_000.0,
+                report.elapsed.as_secs_f64() * 1_000.0 / report.committed.max(1) as f64
+            );
+        }
+        Err(error) => println!("File backend unavailable: {}", error),
+    }
+    
+    // Reopen from disk, as after a restart
+    let reopen_start = Instant::now();
+    match FileBackend::open(&storage_dir).and_then(|backend| EncryptedStore::load(COUNTER_CONTRACT_ID, &backend)) {
+        Ok(Some(store)) => println!("Reopened {} slot(s) in {}μs", store.len(), reopen_start.elapsed().as_micros()),
+        Ok(None) => println!("Nothing persisted to reopen"),
+        Err(error) => println!("Reopen failed: {}", error),
+    }
+    let _ = fs::remove_dir_all(&storage_dir);
+    
+    // Malicious security: a deviating party must be caught and named
+    println!("\n=== MPC Fault Injection ===");
+    let mut cheating_auction = SealedBidAuction::new(3, 5).with_faults(&[(2, MpcFault::WrongProduct)]);
+    for bid in bids {
+        if let Err(error) = cheating_auction.submit_bid(bid) {
+            println!("Bid {} rejected: {}", bid, error);
+        }
+    }
+    let (auction_outcome, _) = cheating_auction.run_mpc();
+    
+    let mut cheating_counter = EncryptedCounter::new(41, key).with_mpc_faults(&[(4, MpcFault::WrongShare)]);
+    if let Err(error) = cheating_counter.increment(ExecutionModel::MPCBased, key) {
+        println!("Counter increment aborted: {}", error);
+    }
+    let reveal_outcome = cheating_counter.reveal_mpc(key);
+    
+    for (label, outcome) in [
+        ("Auction, party 2 sends wrong products", auction_outcome.map(|o| o.winning_bid)),
+        ("Counter reveal, party 4 sends wrong shares", reveal_outcome),
+    ] {
+        match outcome {
+            Err(e) => println!("{}: aborted ({})", label, e),
+            Ok(value) => println!("{}: returned {}", label, value),
+        }
+    }
+    
+    // Optimistic TEE: accepted at once, final after the window unless a
+    // re-executing verifier proves the transition wrong
+    println!("\n=== Optimistic TEE (fraud proofs) ===");
+    let config = OptimisticConfig::default();
+    println!(
+        "Window {}s, dispute period {}s, bond {}",
+        config.window.as_secs(),
+        config.dispute_period.as_secs(),
+        config.bond
+    );
+    // Everyone starts with one bond; returns the balances after settlement,
+    // executor first
+    let funded = |config: OptimisticConfig, behaviors: &[VerifierBehavior]| {
+        let mut tee = OptimisticTee::new(EncryptedCounter::new(41, key), config, behaviors, key)?;
+        tee.deposit(BondAccount::Executor, config.bond);
+        (0..behaviors.len()).for_each(|verifier| tee.deposit(BondAccount::Verifier(verifier), config.bond));
+        Ok::<_, ExecutionError>(tee)
+    };
+    let balances = |tee: &OptimisticTee, verifiers: usize| {
+        let accounts = std::iter::once(BondAccount::Executor).chain((0..verifiers).map(BondAccount::Verifier));
+        accounts.map(|account| tee.balance(account)).collect::<Vec<_>>()
+    };
+    let settle_bonded = |behaviors: &[VerifierBehavior], forged: Option<u64>| {
+        let mut tee = funded(config, behaviors).ok()?;
+        tee.request(key).ok()?;
+        let posted = tee.post(key, forged).ok()?;
+        let settlement = tee.submit(posted).ok()?;
+        Some((settlement, tee.reveal(key), balances(&tee, behaviors.len())))
+    };
+    
+    let unchallenged = settle_bonded(&[VerifierBehavior::Honest; 3], None);
+    let griefed = settle_bonded(&[VerifierBehavior::Honest, VerifierBehavior::Griefing], None);
+    for (label, outcome) in [("Valid, no challenge", &unchallenged), ("Valid, griefing challenge", &griefed)] {
+        if let Some((settlement, _, bonds)) = outcome {
+            println!(
+                "{}: final after {:.3}s (simulated), bonds {:?}",
+                label,
+                settlement.time_to_finality().as_secs_f64(),
+                bonds
+            );
+        }
+    }
+    
+    // One honest verifier among colluders, in every position
+    let verifiers = 5;
+    let fraud_trials: Vec<_> = (0..2 * verifiers)
+        .map(|trial| {
+            let mut behaviors = vec![VerifierBehavior::Silent; verifiers];
+            behaviors[trial % verifiers] = VerifierBehavior::Honest;
+            settle_bonded(&behaviors, Some(43 + trial as u64))
+        })
+        .collect();
+    let caught = fraud_trials
+        .iter()
+        .enumerate()
+        .filter(|(trial, outcome)| {
+            matches!(outcome, Some((Settlement::Reverted { challenger, .. }, Some(41), _)) if *challenger == trial % verifiers)
+        })
+        .count();
+    if let Some(Some((fraud, _, bonds))) = fraud_trials.first() {
+        println!(
+            "Invalid, one honest verifier: reverted after {:.3}s (simulated), bonds {:?}",
+            fraud.time_to_finality().as_secs_f64(),
+            bonds
+        );
+    }
+    println!("Forged transitions caught: {} of {}", caught, fraud_trials.len());
+    
+    // Summary and recommendation
+    println!("\n=== VALIDATION SUMMARY ===");
+    
+    let Some(best) = results.iter().filter(|r| r.success).min_by_key(|r| r.total_time_us) else {
+        println!("❌ No model completed an increment");
+        return;
+    };
+    
+    println!("Best model: {:?}", best.model);
+    println!("Best overhead: {:.1}×", best.overhead_vs_plaintext(&counter_baseline));
+    
+    if best.meets_target(&counter_baseline, OVERHEAD_TARGET) {
+        println!("✅ Core Assumption 2: VALIDATED");
+        println!("Recommended approach: {:?}", best.model);
+    } else {
+        println!("❌ Core Assumption 2: FAILED");
+        println!("Minimum overhead: {:.1}× (target: <{}×)", 
+                 best.overhead_vs_plaintext(&counter_baseline), OVERHEAD_TARGET);
+        println!("\nRecommendation: Use TEE-based for v1, FHE (LWE) for additive-only contracts in v2");
+    }
+}
+
+fn main() {
+    run_execution_benchmark();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::zk::random_blinding;
+    
+    // Fixtures shared with the module tests
+    pub(crate) const KEY: &[u8] = b"test_encryption_key_32_bytes!!!";
+    
+    // Two contracts × two epochs × two slots, sealed under `master`
+    pub(crate) fn sealed_tree(master: &ViewingKey) -> Vec<(KeyPath, String, EncryptedState)> {
+        let mut states = Vec::new();
+        for contract in ["counter", "ledger"] {
+            for epoch in 0..2 {
+                for slot in 0..2 {
+                    let path = KeyPath::contract(contract).epoch(epoch).slot(slot);
+                    let plaintext = format!("{}/{}/{}", contract, epoch, slot);
+                    let state = EncryptedState::at(path.clone(), plaintext.as_bytes(), master).expect("sealed");
+                    states.push((path, plaintext, state));
+                }
+            }
+        }
+        states
+    }
+    
+    // A guest around `body` with the host imports and one page of memory
+    pub(crate) fn wasm_guest(body: &str) -> String {
+        format!(
+            r#"(module
+              (import "ecliptica" "state_read" (func $read (param i64 i32 i32) (result i32)))
+              (import "ecliptica" "state_write" (func $write (param i64 i32 i32)))
+              (memory (export "memory") 1)
+              (func (export "call") (param $arg i64) {}))"#,
+            body
+        )
+    }
+    
+    // slot 0 += 2 · arg 0
+    pub(crate) fn scaled_add() -> Program {
+        Program::new(vec![
+            Instruction::Load(COUNTER_SLOT),
+            Instruction::Arg(0),
+            Instruction::Push(2),
+            Instruction::Mul,
+            Instruction::Add,
+            Instruction::Store(COUNTER_SLOT),
+            Instruction::Halt,
+        ])
+    }
+    
+    // A model that changes the value leaves a fresh commitment the provers
+    // can start from: TEE then ZK, MPC then Hybrid, FHE then ZK
+    #[test]
+    fn provers_continue_after_every_model() {
+        let mut counter = EncryptedCounter::new(41, KEY);
+        counter.prepare(ExecutionModel::TEEBased, KEY).expect("deployment");
+        let before = counter.value_commitment();
+        counter.increment(ExecutionModel::TEEBased, KEY).expect("TEE increment");
+        assert_ne!(counter.value_commitment(), before, "TEE re-commits");
+        counter.increment(ExecutionModel::ClientSideZK, KEY).expect("ZK after TEE");
+        assert_eq!(counter.decrypt_value(counter.state(), KEY), Ok(43));
+        
+        let mut counter = EncryptedCounter::new(41, KEY);
+        counter.increment(ExecutionModel::MPCBased, KEY).expect("MPC increment");
+        assert_eq!(counter.reveal_mpc(KEY), Ok(42));
+        counter.prepare(ExecutionModel::HybridTEEZK, KEY).expect("deployment");
+        counter.increment(ExecutionModel::HybridTEEZK, KEY).expect("Hybrid after MPC");
+        assert_eq!(counter.decrypt_value(counter.state(), KEY), Ok(43));
+        
+        let mut counter = EncryptedCounter::new(41, KEY);
+        counter.prepare(ExecutionModel::FHEBased, KEY).expect("deployment");
+        counter.increment(ExecutionModel::FHEBased, KEY).expect("FHE increment");
+        assert_eq!(counter.reveal_fhe(KEY), Some(42));
+        counter.increment(ExecutionModel::ClientSideZK, KEY).expect("ZK after FHE");
+        assert_eq!(counter.decrypt_value(counter.state(), KEY), Ok(43));
+    }
+    
+    // Each model's evidence checks out for its own transition and no other
+    #[test]
+    fn evidence_validates_only_its_transition() {
+        for model in ExecutionModel::ALL {
+            let mut counter = EncryptedCounter::new(41, KEY);
+            counter.prepare(model, KEY).expect("deployment");
+            let validator = counter.validator();
+            let previous = counter.state_commitment();
+            counter.increment(model, KEY).unwrap_or_else(|error| panic!("{:?}: {}", model, error));
+            let evidence = counter.evidence().cloned().expect("evidence");
+            let new_state = counter.state().clone();
+            let mut tampered = new_state.clone();
+            tampered.ciphertext[0] ^= 1;
+            let tampered = EncryptedState::from_ciphertext(tampered.ciphertext);
+            let replayed = counter.state_commitment();
+            assert!(validator.validate(&previous, &new_state, &evidence).is_ok(), "{:?}: valid transition accepted", model);
+            assert!(validator.validate(&previous, &tampered, &evidence).is_err(), "{:?}: substituted ciphertext rejected", model);
+            assert!(validator.validate(&replayed, &new_state, &evidence).is_err(), "{:?}: replayed on newer state rejected", model);
+        }
+    }
+    
+    #[test]
+    fn mpc_transcript_needs_threshold_distinct_signers() {
+        let mut counter = EncryptedCounter::new(41, KEY);
+        let validator = counter.validator();
+        let previous = counter.state_commitment();
+        counter.increment(ExecutionModel::MPCBased, KEY).expect("MPC increment");
+        let Some(ExecutionEvidence::MpcTranscript { signatures, call }) = counter.evidence().cloned() else {
+            panic!("MPCBased produced no transcript");
+        };
+        let below_threshold = ExecutionEvidence::MpcTranscript { signatures: signatures[..2].to_vec(), call };
+        let duplicated = ExecutionEvidence::MpcTranscript { signatures: vec![signatures[0].clone(); 3], call };
+        assert!(validator.validate(&previous, counter.state(), &below_threshold).is_err(), "2 of 3 signatures rejected");
+        assert!(validator.validate(&previous, counter.state(), &duplicated).is_err(), "one party signing three times rejected");
+    }
+    
+    // Stale transitions are rejected and retried: every increment lands once
+    #[test]
+    fn concurrent_clients_land_every_increment_once() {
+        let (clients, increments) = (4, 3);
+        for model in ExecutionModel::ALL {
+            let mut counter = EncryptedCounter::new(0, KEY);
+            counter.prepare(model, KEY).expect("deployment");
+            let host = ContractHost::new(counter);
+            let report = host.run_load(model, clients, increments, KEY);
+            assert_eq!(report.committed, clients * increments, "{:?}", model);
+            assert_eq!(host.reveal(KEY), Some((clients * increments) as u64), "{:?}", model);
+        }
+    }
+    
+    // The counter lives in a single slot under every model
+    #[test]
+    fn counter_runs_touch_one_slot() {
+        for model in ExecutionModel::ALL {
+            let result = EncryptedCounter::new(0, KEY).increment(model, KEY).expect("increment");
+            assert_eq!(result.slots_touched, 1, "{:?}", model);
+        }
+    }
+    
+    #[test]
+    fn host_log_holds_one_event_per_commit() {
+        let host = ContractHost::new(EncryptedCounter::new(0, KEY));
+        let load = host.run_load(ExecutionModel::ClientSideZK, 2, 2, KEY);
+        assert_eq!(load.committed, 4);
+        assert_eq!(host.events().len(), 4);
+        assert_eq!(scan_events::<CounterCalled>(&host.events(), &EncryptedCounter::viewing_key(KEY)).len(), 4);
+    }
+    
+    // Failures surface as typed errors and leave the state as it was
+    #[test]
+    fn wrong_key_is_a_decrypt_failure_and_keeps_the_state() {
+        for model in ExecutionModel::ALL {
+            let mut counter = EncryptedCounter::new(41, KEY);
+            let before = counter.state_commitment();
+            assert_eq!(counter.increment(model, b"wrong key").err(), Some(ExecutionError::DecryptFailed), "{:?}", model);
+            assert!(counter.state_commitment() == before, "{:?}: state kept", model);
+        }
+    }
+    
+    // States as a client or enclave might read them off a faulty node
+    #[test]
+    fn bad_states_reported_with_their_own_error() {
+        let mut counter = EncryptedCounter::new(41, KEY);
+        assert!(counter.prepare_enclave(KEY));
+        let mut corrupted = counter.state().clone();
+        corrupted.ciphertext[AEAD_OVERHEAD] ^= 1;
+        let malformed = counter.seal(KEY, &[1, 2, 3, 4], &PaddingPolicy::None);
+        for model in [ExecutionModel::ClientSideZK, ExecutionModel::TEEBased] {
+            for (state, expected) in [(&corrupted, ExecutionError::DecryptFailed), (&malformed, ExecutionError::MalformedState)] {
+                let mut snapshot = counter.snapshot();
+                snapshot.store.write(COUNTER_SLOT, state.clone());
+                let (result, next) = counter.execute(model, &snapshot, KEY);
+                assert!(!result.success && next.is_err(), "{:?}", model);
+                assert_eq!(result.error, Some(expected), "{:?}", model);
+            }
+        }
+    }
+    
+    // Each applicable fault at 10% of 40 calls per model
+    pub(crate) fn fault_report(model: ExecutionModel) -> RobustnessReport {
+        let mut injector = InjectedFault::ALL
+            .into_iter()
+            .fold(FaultInjector::new(48), |injector, fault| injector.with_rate(fault, 0.1));
+        let mut counter = EncryptedCounter::new(41, KEY);
+        counter.prepare(model, KEY).expect("deployment");
+        injector.run(&counter, model, 40, KEY)
+    }
+    
+    #[test]
+    fn every_injected_fault_fails_with_its_own_error() {
+        for model in ExecutionModel::ALL {
+            let report = fault_report(model);
+            assert_eq!(report.failures.len(), report.injected, "{:?}", model);
+            assert!(report.failures_explained(), "{:?}", model);
+        }
+    }
+    
+    #[test]
+    fn corrupted_state_fails_before_proving() {
+        let report = fault_report(ExecutionModel::ClientSideZK);
+        let mean_success = report.mean_success_us().expect("successful calls");
+        let (_, failure_us) = report.failure_path(InjectedFault::CorruptedCiphertext).expect("corruption injected");
+        assert!(failure_us < mean_success, "{}μs to fail, {}μs to succeed", failure_us, mean_success);
+    }
+    
+    // Hybrid: accepted on the enclave's signature before the prover runs,
+    // its commitments upheld on challenge
+    #[test]
+    fn hybrid_accepts_before_proving_and_upholds_challenges() {
+        let mut counter = EncryptedCounter::new(41, KEY);
+        let validator = counter.validator();
+        for _ in 0..3 {
+            let previous = counter.state_commitment();
+            let result = counter.increment(ExecutionModel::HybridTEEZK, KEY).expect("hybrid increment");
+            let latency = result.hybrid_latency.expect("hybrid latency");
+            let evidence = counter.evidence().cloned().expect("evidence");
+            assert!(validator.validate(&previous, counter.state(), &evidence).is_ok(), "accepted");
+            assert_eq!(validator.challenge(&evidence), Ok(()), "upheld");
+            let proof_us = latency.proof_us.expect("proved within the deadline");
+            assert!(latency.optimistic_us + proof_us <= result.total_time_us, "optimistic latency leaves the prover out");
+            let window = HYBRID_CHALLENGE_WINDOW.as_micros() as u64;
+            assert_eq!(latency.challenge_us(HYBRID_CHALLENGE_WINDOW, 1), Some(window + proof_us + 1));
+        }
+        counter.increment(ExecutionModel::ClientSideZK, KEY).expect("ZK continues from the hybrid value commitment");
+        assert_eq!(ContractHost::new(counter).reveal(KEY), Some(45));
+    }
+    
+    // A prover past its deadline costs the proof, not the call; challenging
+    // the unproven transition reverts it
+    #[test]
+    fn stalled_hybrid_prover_leaves_the_call_open_to_challenge() {
+        let deadline = Duration::from_millis(20);
+        let mut counter = EncryptedCounter::new(41, KEY).with_prover_deadline(deadline);
+        counter.prepare(ExecutionModel::HybridTEEZK, KEY).expect("deployment");
+        let validator = counter.validator();
+        let stall = Some(InjectedFault::ProverTimeout);
+        let (result, next) = counter.execute_with_fault(ExecutionModel::HybridTEEZK, &counter.snapshot(), KEY, stall);
+        assert!(result.success, "{:?}", result.error);
+        assert!(result.hybrid_latency.is_some_and(|latency| latency.proof_us.is_none()));
+        let (next, evidence) = next.expect("accepted without the proof");
+        assert!(validator.validate(&counter.state_commitment(), next.state(), &evidence).is_ok(), "accepted unproven");
+        assert_eq!(validator.challenge(&evidence), Err(Rejection::MissingProof));
+    }
+    
+    #[test]
+    fn forged_value_commitment_passes_optimistically_and_fails_its_challenge() {
+        let mut counter = EncryptedCounter::new(41, KEY);
+        let validator = counter.validator();
+        let previous = counter.state_commitment();
+        counter.increment(ExecutionModel::HybridTEEZK, KEY).expect("hybrid increment");
+        let Some(ExecutionEvidence::Hybrid { report, signature, call, transition, proof }) = counter.evidence().cloned() else {
+            panic!("HybridTEEZK produced no hybrid evidence");
+        };
+        let forged = ExecutionEvidence::Hybrid {
+            report,
+            signature,
+            call,
+            transition: CounterTransition { new_commitment: value_commitment(100, random_blinding(), &transition.new_state), ..transition },
+            proof,
+        };
+        assert!(validator.validate(&previous, counter.state(), &forged).is_ok());
+        assert!(validator.challenge(&forged).is_err());
+    }
+    
+    // Signed over the user's sealed call: the same signature does not
+    // carry over to another one
+    #[test]
+    fn signature_moved_to_another_call_rejected() {
+        for model in [ExecutionModel::TEEBased, ExecutionModel::MPCBased] {
+            let mut counter = EncryptedCounter::new(41, KEY);
+            counter.prepare(model, KEY).expect("prepared");
+            let validator = counter.validator();
+            let previous = counter.state_commitment();
+            counter.increment(model, KEY).expect("increment");
+            let evidence = counter.evidence().cloned().expect("evidence");
+            assert!(validator.validate(&previous, counter.state(), &evidence).is_ok(), "{:?}", model);
+            let other_call = match evidence {
+                ExecutionEvidence::Enclave { report, signature, .. } => {
+                    ExecutionEvidence::Enclave { report, signature, call: [0; 32] }
+                }
+                ExecutionEvidence::MpcTranscript { signatures, .. } => {
+                    ExecutionEvidence::MpcTranscript { signatures, call: [0; 32] }
+                }
+                _ => panic!("{:?} produced no signed evidence", model),
+            };
+            assert!(validator.validate(&previous, counter.state(), &other_call).is_err(), "{:?}", model);
+        }
+    }
+}
@@ -0,0 +1,1305 @@
+use ml_dsa::{KeyGen, KeyPair, MlDsa65, Signature, VerifyingKey};
+use ml_dsa::signature::Signer;
+use rand::Rng;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::{ExecutionError, ExecutionModel, ExecutionResult};
+use crate::keys::aead_seal;
+use crate::storage::EncryptedState;
+use crate::tee::transition_payload;
+use crate::timing::{Phase, PhaseTimings};
+use crate::vm::VmWord;
+
+// Prime field for secret sharing (Mersenne prime 2^61 - 1)
+pub const MPC_PRIME: u64 = (1u64 << 61) - 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fp(u64);
+
+impl Fp {
+    pub const ZERO: Fp = Fp(0);
+    pub const ONE: Fp = Fp(1);
+    
+    pub fn new(value: u64) -> Self {
+        Fp(value % MPC_PRIME)
+    }
+    
+    pub fn random() -> Self {
+        Fp(rand::thread_rng().gen_range(0..MPC_PRIME))
+    }
+    
+    pub fn value(self) -> u64 {
+        self.0
+    }
+    
+    pub fn pow(self, mut exp: u64) -> Self {
+        let mut base = self;
+        let mut acc = Fp::ONE;
+        while exp > 0 {
+            if exp & 1 == 1 {
+                acc = acc * base;
+            }
+            base = base * base;
+            exp >>= 1;
+        }
+        acc
+    }
+    
+    pub fn inverse(self) -> Self {
+        // Fermat: a^(p-2) = a^-1 mod p
+        self.pow(MPC_PRIME - 2)
+    }
+}
+
+impl From<u64> for Fp {
+    fn from(value: u64) -> Self {
+        Fp::new(value)
+    }
+}
+
+impl Add for Fp {
+    type Output = Fp;
+    fn add(self, rhs: Fp) -> Fp {
+        Fp::new(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Fp {
+    type Output = Fp;
+    fn sub(self, rhs: Fp) -> Fp {
+        Fp::new(self.0 + MPC_PRIME - rhs.0)
+    }
+}
+
+impl Mul for Fp {
+    type Output = Fp;
+    fn mul(self, rhs: Fp) -> Fp {
+        Fp(((self.0 as u128 * rhs.0 as u128) % MPC_PRIME as u128) as u64)
+    }
+}
+
+impl Neg for Fp {
+    type Output = Fp;
+    fn neg(self) -> Fp {
+        Fp::ZERO - self
+    }
+}
+
+// One party's Shamir share: the sharing polynomial evaluated at x = party + 1
+#[derive(Debug, Clone, Copy)]
+pub struct Share {
+    pub party: usize,
+    pub value: Fp,
+}
+
+impl Share {
+    pub fn x(&self) -> Fp {
+        Fp::new(self.party as u64 + 1)
+    }
+}
+
+// A secret held as Shamir shares (threshold-of-n). Linear operations are
+// share-local: each party applies them to its own share, no reconstruction.
+#[derive(Debug, Clone)]
+pub struct SharedValue {
+    pub threshold: usize,
+    pub shares: Vec<Share>,
+}
+
+impl SharedValue {
+    pub fn share(secret: impl Into<Fp>, threshold: usize, total: usize) -> Self {
+        assert!(threshold >= 1 && threshold <= total, "invalid {}-of-{} sharing", threshold, total);
+        
+        // Random polynomial of degree threshold-1 with f(0) = secret
+        let mut coefficients = vec![secret.into()];
+        coefficients.extend((1..threshold).map(|_| Fp::random()));
+        
+        let shares = (0..total)
+            .map(|party| {
+                let x = Fp::new(party as u64 + 1);
+                let value = coefficients.iter().rev().fold(Fp::ZERO, |acc, c| acc * x + *c);
+                Share { party, value }
+            })
+            .collect();
+        
+        Self { threshold, shares }
+    }
+    
+    // [x] + [y]: each party adds its two shares
+    pub fn add(&self, other: &SharedValue) -> Self {
+        self.zip_with(other, |a, b| a + b)
+    }
+    
+    // [x] - [y]: each party subtracts its two shares
+    pub fn sub(&self, other: &SharedValue) -> Self {
+        self.zip_with(other, |a, b| a - b)
+    }
+    
+    // [x] + c: every party adds the public constant (f(X) + c still has degree t-1)
+    pub fn add_const(&self, c: impl Into<Fp>) -> Self {
+        let c = c.into();
+        self.map(|v| v + c)
+    }
+    
+    // [x] - c
+    pub fn sub_const(&self, c: impl Into<Fp>) -> Self {
+        let c = c.into();
+        self.map(|v| v - c)
+    }
+    
+    // c · [x]: every party scales its share
+    pub fn mul_const(&self, c: impl Into<Fp>) -> Self {
+        let c = c.into();
+        self.map(|v| v * c)
+    }
+    
+    // Parties broadcast their shares; Lagrange interpolation at zero from
+    // the first `threshold` of them
+    pub fn open(&self) -> Option<Fp> {
+        if self.shares.len() < self.threshold {
+            return None;
+        }
+        Some(interpolate_at_zero(&self.shares[..self.threshold]))
+    }
+    
+    pub fn reconstruct(&self) -> Option<u64> {
+        self.open().map(Fp::value)
+    }
+    
+    fn map(&self, f: impl Fn(Fp) -> Fp) -> Self {
+        Self {
+            threshold: self.threshold,
+            shares: self.shares.iter()
+                .map(|s| Share { party: s.party, value: f(s.value) })
+                .collect(),
+        }
+    }
+    
+    fn zip_with(&self, other: &SharedValue, f: impl Fn(Fp, Fp) -> Fp) -> Self {
+        assert_eq!(self.shares.len(), other.shares.len(), "sharings held by different party sets");
+        Self {
+            threshold: self.threshold.max(other.threshold),
+            shares: self.shares.iter().zip(&other.shares)
+                .map(|(a, b)| {
+                    debug_assert_eq!(a.party, b.party);
+                    Share { party: a.party, value: f(a.value, b.value) }
+                })
+                .collect(),
+        }
+    }
+}
+
+pub fn interpolate_at_zero(shares: &[Share]) -> Fp {
+    shares.iter().enumerate().fold(Fp::ZERO, |acc, (i, share_i)| {
+        let (num, den) = shares.iter().enumerate()
+            .filter(|(j, _)| *j != i)
+            .fold((Fp::ONE, Fp::ONE), |(num, den), (_, share_j)| {
+                (num * share_j.x(), den * (share_j.x() - share_i.x()))
+            });
+        acc + share_i.value * num * den.inverse()
+    })
+}
+
+// Simulated per-link network between MPC nodes
+#[derive(Debug, Clone, Copy)]
+pub struct NetworkConfig {
+    pub link_latency: Duration,
+    pub bandwidth_bytes_per_sec: u64,
+    // How long a node waits on a peer before counting it as dropped
+    pub peer_timeout: Duration,
+}
+
+impl NetworkConfig {
+    pub fn lan() -> Self {
+        Self {
+            link_latency: Duration::from_micros(100),
+            bandwidth_bytes_per_sec: 125_000_000, // 1 Gbit/s
+            peer_timeout: Duration::from_millis(200),
+        }
+    }
+    
+    pub fn wan() -> Self {
+        Self {
+            link_latency: Duration::from_millis(20),
+            bandwidth_bytes_per_sec: 12_500_000, // 100 Mbit/s
+            peer_timeout: Duration::from_secs(1),
+        }
+    }
+    
+    fn transmission_time(&self, bytes: usize) -> Duration {
+        Duration::from_nanos(bytes as u64 * 1_000_000_000 / self.bandwidth_bytes_per_sec.max(1))
+    }
+}
+
+// Wire format: sender id + length header, then 8 bytes per field element
+const MESSAGE_HEADER_BYTES: usize = 16;
+
+struct Message {
+    from: usize,
+    payload: Vec<Fp>,
+    deliver_at: Instant,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct PartyStats {
+    pub node: usize,
+    pub rounds: u64,
+    pub messages_sent: u64,
+    pub bytes_sent: u64,
+    pub network_wait_us: u64,
+    // Spent waiting on peers that never answered, until their timeout
+    pub timeout_wait_us: u64,
+    pub dropped_out: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NetworkStats {
+    pub rounds: u64,
+    pub nodes: Vec<PartyStats>, // compute parties, then the client last
+}
+
+impl NetworkStats {
+    pub fn total_bytes(&self) -> u64 {
+        self.nodes.iter().map(|n| n.bytes_sent).sum()
+    }
+    
+    pub fn max_network_wait_us(&self) -> u64 {
+        self.nodes.iter().map(|n| n.network_wait_us).max().unwrap_or(0)
+    }
+    
+    // Longest any node waited before giving up on a dropped peer
+    pub fn detection_us(&self) -> u64 {
+        self.nodes.iter().map(|n| n.timeout_wait_us).max().unwrap_or(0)
+    }
+}
+
+// Authenticated share held by one party, with pairwise information-theoretic
+// MACs (SPDZ-style, BDOZ variant with a key per verifier so a failed check
+// names the sender). For verifier node j with global key alpha_j:
+//   tags[j] = alpha_j · value + (j's key for this share)
+// and keys[i] is this party's key for party i's share of the same secret.
+#[derive(Debug, Clone)]
+pub struct AuthShare {
+    pub value: Fp,
+    pub tags: Vec<Fp>, // one per verifier node (parties, then client)
+    pub keys: Vec<Fp>, // one per compute party
+}
+
+impl AuthShare {
+    // Valid sharing of zero for any MAC keys
+    pub fn zero(parties: usize) -> Self {
+        Self {
+            value: Fp::ZERO,
+            tags: vec![Fp::ZERO; parties + 1],
+            keys: vec![Fp::ZERO; parties],
+        }
+    }
+    
+    pub fn add(&self, other: &AuthShare) -> Self {
+        self.zip_with(other, |a, b| a + b)
+    }
+    
+    pub fn sub(&self, other: &AuthShare) -> Self {
+        self.zip_with(other, |a, b| a - b)
+    }
+    
+    pub fn scale(&self, c: Fp) -> Self {
+        Self {
+            value: self.value * c,
+            tags: self.tags.iter().map(|t| *t * c).collect(),
+            keys: self.keys.iter().map(|k| *k * c).collect(),
+        }
+    }
+    
+    // Every party adds c to its Shamir share; tags stay, and each verifier
+    // shifts its keys by -alpha·c so the other parties' shifted shares still verify
+    pub fn add_const(&self, c: Fp, party: usize, alpha: Fp) -> Self {
+        let mut out = self.clone();
+        out.value = out.value + c;
+        for (i, key) in out.keys.iter_mut().enumerate() {
+            if i != party {
+                *key = *key - alpha * c;
+            }
+        }
+        out
+    }
+    
+    fn zip_with(&self, other: &AuthShare, f: impl Fn(Fp, Fp) -> Fp) -> Self {
+        Self {
+            value: f(self.value, other.value),
+            tags: self.tags.iter().zip(&other.tags).map(|(a, b)| f(*a, *b)).collect(),
+            keys: self.keys.iter().zip(&other.keys).map(|(a, b)| f(*a, *b)).collect(),
+        }
+    }
+}
+
+// Who applies a public constant to an authenticated share: a compute party,
+// or the client (node `parties`) mirroring it on its MAC keys
+#[derive(Debug, Clone, Copy)]
+pub struct ShareContext {
+    pub party: usize,
+    pub parties: usize,
+    pub(crate) alpha: Fp,
+}
+
+// Linear steps are share-local; a product of two shares needs Beaver
+// triples and a round of communication, which the VM does not do
+impl VmWord for AuthShare {
+    type Context = ShareContext;
+    
+    fn constant(context: &ShareContext, value: u64) -> Self {
+        AuthShare::zero(context.parties).add_const(Fp::new(value), context.party, context.alpha)
+    }
+    
+    fn add(&self, other: &Self) -> Self {
+        AuthShare::add(self, other)
+    }
+    
+    fn sub(&self, other: &Self) -> Self {
+        AuthShare::sub(self, other)
+    }
+    
+    fn mul_public(&self, value: u64) -> Self {
+        self.scale(Fp::new(value))
+    }
+    
+    fn mul(&self, _: &Self) -> Option<Self> {
+        None
+    }
+    
+    fn is_zero(&self) -> Option<bool> {
+        None
+    }
+}
+
+// An authenticated sharing as the dealer hands it out: one share per party,
+// plus the client's MAC keys so the client can verify shares revealed to it
+#[derive(Debug, Clone)]
+pub struct AuthSharing {
+    pub threshold: usize,
+    pub shares: Vec<AuthShare>,
+    pub client_keys: Vec<Fp>,
+}
+
+impl AuthSharing {
+    // Client-side mirror of AuthShare::add_const for its own keys
+    pub fn client_add_const(&mut self, c: Fp, client_alpha: Fp) {
+        for key in &mut self.client_keys {
+            *key = *key - client_alpha * c;
+        }
+    }
+    
+    // The client's keys as a key-only share, so it can mirror a program's
+    // linear steps by running the same program on it
+    pub fn client_view(&self) -> AuthShare {
+        AuthShare { value: Fp::ZERO, tags: Vec::new(), keys: self.client_keys.clone() }
+    }
+    
+    pub fn client_context(&self, client_alpha: Fp) -> ShareContext {
+        let parties = self.shares.len();
+        ShareContext { party: parties, parties, alpha: client_alpha }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MpcFault {
+    WrongShare,   // adds an error to every share it opens
+    WrongProduct, // adds an error to its share of every Beaver product
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MpcError {
+    // Tag on a share opened by `cheating_party` did not verify at `detected_by`
+    MacCheckFailed { cheating_party: usize, detected_by: usize },
+    // Nothing has been dealt to the parties yet
+    NotShared,
+    // `from` hung up, or sent nothing within the peer timeout
+    Disconnected { from: usize },
+    // Fewer than `needed` parties answered; `held` did
+    InsufficientShares { held: usize, needed: usize },
+    // `from` sent a message of the wrong length
+    MalformedMessage { from: usize },
+    // The circuit needs more multiplications than were preprocessed
+    OutOfTriples,
+    // Operands of a bitwise comparison have different widths
+    WidthMismatch { left: usize, right: usize },
+    // An input does not fit the circuit's bit width
+    InputOutOfRange { value: u64, bits: usize },
+    // A node's task panicked instead of returning
+    NodePanicked { node: usize },
+}
+
+impl std::fmt::Display for MpcError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MpcError::MacCheckFailed { cheating_party, detected_by } => write!(
+                f, "MAC check failed: party {} sent a bad share (detected by node {})",
+                cheating_party, detected_by
+            ),
+            MpcError::NotShared => write!(f, "no shared state held by the MPC parties"),
+            MpcError::Disconnected { from } => write!(f, "node {} dropped out", from),
+            MpcError::InsufficientShares { held, needed } => write!(f, "{} parties answered, {} needed", held, needed),
+            MpcError::MalformedMessage { from } => write!(f, "node {} sent a malformed message", from),
+            MpcError::OutOfTriples => write!(f, "preprocessing produced too few Beaver triples"),
+            MpcError::WidthMismatch { left, right } => write!(f, "comparing {}-bit and {}-bit values", left, right),
+            MpcError::InputOutOfRange { value, bits } => write!(f, "input {} does not fit in {} bits", value, bits),
+            MpcError::NodePanicked { node } => write!(f, "node {} panicked", node),
+        }
+    }
+}
+
+// Opened share waiting for the output-time MAC check
+struct PendingCheck {
+    from: usize,
+    value: Fp,
+    tag: Fp,
+    key: Fp,
+}
+
+// A node's view of the network while its task runs. Nodes 0..parties are
+// the compute parties, node `parties` is the client (input owner).
+pub struct PartyContext {
+    pub id: usize,
+    pub parties: usize,
+    pub threshold: usize,
+    alpha: Fp,
+    fault: Option<MpcFault>,
+    config: NetworkConfig,
+    // None for the node itself, so its inbox closes once every peer is gone
+    outboxes: Vec<Option<Sender<Message>>>,
+    inbox: Receiver<Message>,
+    stashed: Vec<Message>,
+    link_free_at: Vec<Instant>,
+    pending_checks: Vec<PendingCheck>,
+    stats: PartyStats,
+}
+
+impl PartyContext {
+    pub fn share_context(&self) -> ShareContext {
+        ShareContext { party: self.id, parties: self.parties, alpha: self.alpha }
+    }
+    
+    pub fn client_id(&self) -> usize {
+        self.parties
+    }
+    
+    pub fn next_round(&mut self) {
+        self.stats.rounds += 1;
+    }
+    
+    // Messages on a link are serialised by its bandwidth, then delayed by its latency
+    pub fn send(&mut self, to: usize, payload: Vec<Fp>) {
+        let bytes = MESSAGE_HEADER_BYTES + payload.len() * 8;
+        let now = Instant::now();
+        let start = self.link_free_at[to].max(now);
+        self.link_free_at[to] = start + self.config.transmission_time(bytes);
+        let deliver_at = self.link_free_at[to] + self.config.link_latency;
+        
+        self.stats.messages_sent += 1;
+        self.stats.bytes_sent += bytes as u64;
+        // Receiver may already have finished its task; nothing to deliver then
+        if let Some(outbox) = &self.outboxes[to] {
+            let _ = outbox.send(Message { from: self.id, payload, deliver_at });
+        }
+    }
+    
+    // Next message from `from` (links are FIFO), waiting out its delivery time
+    pub fn recv(&mut self, from: usize) -> Result<Vec<Fp>, MpcError> {
+        self.recv_until(from, Instant::now() + self.config.peer_timeout)
+    }
+    
+    // As recv, but `from` counts as dropped once `deadline` passes, so a
+    // round waits out one timeout however many peers are gone
+    fn recv_until(&mut self, from: usize, deadline: Instant) -> Result<Vec<Fp>, MpcError> {
+        let wait_start = Instant::now();
+        let message = match self.stashed.iter().position(|m| m.from == from) {
+            Some(pos) => self.stashed.remove(pos),
+            None => loop {
+                let timeout = deadline.saturating_duration_since(Instant::now());
+                let Ok(message) = self.inbox.recv_timeout(timeout) else {
+                    self.stats.timeout_wait_us += wait_start.elapsed().as_micros() as u64;
+                    return Err(MpcError::Disconnected { from });
+                };
+                if message.from == from {
+                    break message;
+                }
+                self.stashed.push(message);
+            },
+        };
+        if let Some(delay) = message.deliver_at.checked_duration_since(Instant::now()) {
+            thread::sleep(delay);
+        }
+        self.stats.network_wait_us += wait_start.elapsed().as_micros() as u64;
+        Ok(message.payload)
+    }
+    
+    // Next message from `from`, which must carry exactly `len` elements
+    fn recv_exact(&mut self, from: usize, len: usize) -> Result<Vec<Fp>, MpcError> {
+        self.recv_exact_until(from, len, Instant::now() + self.config.peer_timeout)
+    }
+    
+    fn recv_exact_until(&mut self, from: usize, len: usize, deadline: Instant) -> Result<Vec<Fp>, MpcError> {
+        let payload = self.recv_until(from, deadline)?;
+        if payload.len() != len {
+            return Err(MpcError::MalformedMessage { from });
+        }
+        Ok(payload)
+    }
+    
+    pub fn add_const(&self, share: &AuthShare, c: Fp) -> AuthShare {
+        share.add_const(c, self.id, self.alpha)
+    }
+    
+    // Authenticated public constant (a valid sharing of zero, shifted by c)
+    pub fn constant(&self, c: Fp) -> AuthShare {
+        self.add_const(&AuthShare::zero(self.parties), c)
+    }
+    
+    // Input phase: owner broadcasts x - r for a preprocessed mask [r];
+    // parties then hold [x] = [r] + (x - r)
+    pub fn receive_masked_inputs(&mut self, masks: &[AuthShare]) -> Result<Vec<AuthShare>, MpcError> {
+        let client = self.client_id();
+        let deltas = self.recv_exact(client, masks.len())?;
+        Ok(masks.iter().zip(deltas)
+            .map(|(mask, delta)| self.add_const(mask, delta))
+            .collect())
+    }
+    
+    // One round: send each other party our share of several values with the
+    // tag for that receiver, then interpolate each value from the first
+    // `threshold` parties that answer. Received tags are checked later, at
+    // output.
+    pub fn open_many(&mut self, shares: &[AuthShare]) -> Result<Vec<Fp>, MpcError> {
+        self.next_round();
+        let me = self.id;
+        let error = if self.fault == Some(MpcFault::WrongShare) { Fp::ONE } else { Fp::ZERO };
+        for to in (0..self.parties).filter(|p| *p != me) {
+            let payload = shares.iter()
+                .flat_map(|s| [s.value + error, s.tags[to]])
+                .collect();
+            self.send(to, payload);
+        }
+        
+        let mut received: Vec<Vec<Share>> = vec![Vec::new(); shares.len()];
+        let (deadline, mut answered) = (Instant::now() + self.config.peer_timeout, 1);
+        for from in 0..self.parties {
+            if from == me {
+                for (slot, own) in received.iter_mut().zip(shares) {
+                    slot.push(Share { party: from, value: own.value });
+                }
+                continue;
+            }
+            let payload = match self.recv_exact_until(from, 2 * shares.len(), deadline) {
+                Ok(payload) => payload,
+                Err(MpcError::Disconnected { .. }) => continue,
+                Err(error) => return Err(error),
+            };
+            answered += 1;
+            for ((slot, own), pair) in received.iter_mut().zip(shares).zip(payload.chunks(2)) {
+                let (value, tag) = (pair[0], pair[1]);
+                self.pending_checks.push(PendingCheck { from, value, tag, key: own.keys[from] });
+                slot.push(Share { party: from, value });
+            }
+        }
+        if answered < self.threshold {
+            return Err(MpcError::InsufficientShares { held: answered, needed: self.threshold });
+        }
+        
+        Ok(received.iter()
+            .map(|s| interpolate_at_zero(&s[..self.threshold]))
+            .collect())
+    }
+    
+    // Output-time MAC check over every share opened to this node so far
+    pub fn check_macs(&mut self) -> Result<(), MpcError> {
+        let alpha = self.alpha;
+        let detected_by = self.id;
+        let failed = self.pending_checks.drain(..)
+            .find(|c| c.tag != alpha * c.value + c.key);
+        match failed {
+            Some(check) => Err(MpcError::MacCheckFailed { cheating_party: check.from, detected_by }),
+            None => Ok(()),
+        }
+    }
+    
+    // Party side of a round the client waits on: tell it this party is
+    // still online
+    pub fn acknowledge(&mut self) {
+        let client = self.client_id();
+        self.send(client, Vec::new());
+    }
+    
+    // Client side: count the parties that acknowledged within one timeout.
+    // Fewer than the threshold leave nothing to compute with; more but not
+    // all still lose the dropped parties' shares.
+    pub fn await_acknowledgements(&mut self) -> Result<(), MpcError> {
+        let deadline = Instant::now() + self.config.peer_timeout;
+        let mut dropped = None;
+        let mut answered = 0;
+        for party in 0..self.parties {
+            match self.recv_exact_until(party, 0, deadline) {
+                Ok(_) => answered += 1,
+                Err(MpcError::Disconnected { from }) => dropped = dropped.or(Some(from)),
+                Err(error) => return Err(error),
+            }
+        }
+        match dropped {
+            _ if answered < self.threshold => Err(MpcError::InsufficientShares { held: answered, needed: self.threshold }),
+            Some(from) => Err(MpcError::Disconnected { from }),
+            None => Ok(()),
+        }
+    }
+    
+    // Private output: send our share and its client tag to the owner only
+    pub fn reveal_to_client(&mut self, share: &AuthShare) {
+        self.next_round();
+        let client = self.client_id();
+        let error = if self.fault == Some(MpcFault::WrongShare) { Fp::ONE } else { Fp::ZERO };
+        self.send(client, vec![share.value + error, share.tags[client]]);
+    }
+    
+    // Client side of reveal_to_client: verify every party's tag, then interpolate
+    pub fn receive_revealed(&mut self, client_keys: &[Fp]) -> Result<Fp, MpcError> {
+        let mut shares = Vec::with_capacity(self.parties);
+        for (party, key) in client_keys.iter().copied().enumerate() {
+            let payload = self.recv_exact(party, 2)?;
+            self.pending_checks.push(PendingCheck {
+                from: party,
+                value: payload[0],
+                tag: payload[1],
+                key,
+            });
+            shares.push(Share { party, value: payload[0] });
+        }
+        self.check_macs()?;
+        Ok(interpolate_at_zero(&shares[..self.threshold]))
+    }
+    
+    // Beaver multiplication of every pair in a single round: open the masked
+    // d = x - a and e = y - b, then [x·y] = [c] + d·[b] + e·[a] + d·e
+    pub fn mul_many(&mut self, pairs: &[(AuthShare, AuthShare)], triples: &mut Vec<BeaverTriple>) -> Result<Vec<AuthShare>, MpcError> {
+        let used = triples.split_off(triples.len().checked_sub(pairs.len()).ok_or(MpcError::OutOfTriples)?);
+        
+        let masked: Vec<AuthShare> = pairs.iter().zip(&used)
+            .flat_map(|((x, y), t)| [x.sub(&t.a), y.sub(&t.b)])
+            .collect();
+        let opened = self.open_many(&masked)?;
+        
+        let error = if self.fault == Some(MpcFault::WrongProduct) { Fp::ONE } else { Fp::ZERO };
+        Ok(used.iter().zip(opened.chunks(2))
+            .map(|(t, de)| {
+                let (d, e) = (de[0], de[1]);
+                let linear = t.c.add(&t.b.scale(d)).add(&t.a.scale(e));
+                let mut product = self.add_const(&linear, d * e);
+                product.value = product.value + error;
+                product
+            })
+            .collect())
+    }
+    
+    pub fn mul(&mut self, x: &AuthShare, y: &AuthShare, triples: &mut Vec<BeaverTriple>) -> Result<AuthShare, MpcError> {
+        Ok(self.mul_many(&[(x.clone(), y.clone())], triples)?.remove(0))
+    }
+    
+    // [a > b] for bitwise-shared inputs (LSB first). Scanning up from the LSB,
+    // gt = a_k·(1 - b_k) + eq_k·gt, with eq_k = 1 - a_k - b_k + 2·a_k·b_k.
+    // All a_k·b_k products share one round; the eq_k·gt chain is sequential.
+    pub fn greater_than(&mut self, a_bits: &[AuthShare], b_bits: &[AuthShare], triples: &mut Vec<BeaverTriple>) -> Result<AuthShare, MpcError> {
+        if a_bits.len() != b_bits.len() || a_bits.is_empty() {
+            return Err(MpcError::WidthMismatch { left: a_bits.len(), right: b_bits.len() });
+        }
+        
+        let pairs: Vec<(AuthShare, AuthShare)> = a_bits.iter().cloned().zip(b_bits.iter().cloned()).collect();
+        let products = self.mul_many(&pairs, triples)?;
+        
+        let mut gt = a_bits[0].sub(&products[0]);
+        for k in 1..a_bits.len() {
+            let eq = self.add_const(&products[k].scale(Fp::new(2)).sub(&a_bits[k]).sub(&b_bits[k]), Fp::ONE);
+            let carried = self.mul(&eq, &gt, triples)?;
+            gt = a_bits[k].sub(&products[k]).add(&carried);
+        }
+        Ok(gt)
+    }
+    
+    // [cond ? x : y] = [y] + [cond]·([x] - [y]) for each pair, in one round
+    pub fn select_many(&mut self, cond: &AuthShare, pairs: &[(AuthShare, AuthShare)], triples: &mut Vec<BeaverTriple>) -> Result<Vec<AuthShare>, MpcError> {
+        let diffs: Vec<(AuthShare, AuthShare)> = pairs.iter().map(|(x, y)| (cond.clone(), x.sub(y))).collect();
+        Ok(self.mul_many(&diffs, triples)?.into_iter()
+            .zip(pairs)
+            .map(|(scaled, (_, y))| y.add(&scaled))
+            .collect())
+    }
+}
+
+// Runs one task per compute party plus a client task, connected by channels
+pub struct MpcNetwork {
+    pub config: NetworkConfig,
+    pub threshold: usize,
+    pub parties: usize,
+    mac_keys: Vec<Fp>,
+    faults: Vec<(usize, MpcFault)>,
+    dropouts: Vec<usize>,
+}
+
+impl MpcNetwork {
+    // MAC keys come from the dealer that authenticated the parties' shares
+    pub fn new(config: NetworkConfig, dealer: &TrustedDealer) -> Self {
+        Self {
+            config,
+            threshold: dealer.threshold,
+            parties: dealer.total,
+            mac_keys: dealer.mac_keys.clone(),
+            faults: Vec::new(),
+            dropouts: Vec::new(),
+        }
+    }
+    
+    // Fault-injection mode: the listed parties deviate from the protocol
+    pub fn with_faults(mut self, faults: &[(usize, MpcFault)]) -> Self {
+        self.faults = faults.to_vec();
+        self
+    }
+    
+    // The listed parties stop before their program runs: they send nothing
+    // and their peers only find out by timing out on them
+    pub fn with_dropouts(mut self, parties: &[usize]) -> Self {
+        self.dropouts = parties.to_vec();
+        self
+    }
+    
+    // Outputs are the online parties', in party order; dropped parties have none
+
+    pub fn run<T, C>(
+        &self,
+        party_program: impl Fn(&mut PartyContext) -> T + Sync,
+        client_program: impl FnOnce(&mut PartyContext) -> C + Send,
+    ) -> Result<(Vec<T>, C, NetworkStats), MpcError>
+    where
+        T: Send,
+        C: Send,
+    {
+        let nodes = self.parties + 1;
+        let (outboxes, inboxes): (Vec<_>, Vec<_>) = (0..nodes).map(|_| mpsc::channel()).unzip();
+        let now = Instant::now();
+        
+        let mut contexts: Vec<PartyContext> = inboxes.into_iter().enumerate()
+            .map(|(id, inbox)| PartyContext {
+                id,
+                parties: self.parties,
+                threshold: self.threshold,
+                alpha: self.mac_keys[id],
+                fault: self.faults.iter().find(|(p, _)| *p == id).map(|(_, f)| *f),
+                config: self.config,
+                outboxes: outboxes.iter()
+                    .enumerate()
+                    .map(|(to, outbox)| (to != id).then(|| outbox.clone()))
+                    .collect(),
+                inbox,
+                stashed: Vec::new(),
+                link_free_at: vec![now; nodes],
+                pending_checks: Vec::new(),
+                stats: PartyStats { node: id, ..Default::default() },
+            })
+            .collect();
+        drop(outboxes);
+        let mut client_ctx = contexts.pop().expect("network has a client node");
+        
+        thread::scope(|scope| {
+            let party_program = &party_program;
+            // A dropped party runs nothing, but its links stay up until the
+            // end: to its peers it is silent, not hung up
+            let (silent, online): (Vec<_>, Vec<_>) = contexts.into_iter().partition(|ctx| self.dropouts.contains(&ctx.id));
+            let mut party_tasks: Vec<_> = online.into_iter()
+                .map(|mut ctx| (ctx.id, scope.spawn(move || {
+                    let output = party_program(&mut ctx);
+                    (Some(output), ctx.stats)
+                })))
+                .collect();
+            party_tasks.extend(silent.iter().map(|ctx| {
+                let stats = PartyStats { dropped_out: true, ..ctx.stats.clone() };
+                (ctx.id, scope.spawn(move || (None, stats)))
+            }));
+            party_tasks.sort_by_key(|(node, _)| *node);
+            let client_task = scope.spawn(move || {
+                let output = client_program(&mut client_ctx);
+                (output, client_ctx.stats)
+            });
+            
+            // Every node is joined before a panic is reported
+            let joined: Vec<_> = party_tasks.into_iter().map(|(_, task)| task.join()).collect();
+            let client_joined = client_task.join();
+            drop(silent);
+            let mut outputs = Vec::with_capacity(self.parties);
+            let mut stats = NetworkStats::default();
+            for (node, joined) in joined.into_iter().enumerate() {
+                let (output, party_stats) = joined.map_err(|_| MpcError::NodePanicked { node })?;
+                outputs.extend(output);
+                stats.nodes.push(party_stats);
+            }
+            let (client_output, client_stats) = client_joined.map_err(|_| MpcError::NodePanicked { node: self.parties })?;
+            stats.nodes.push(client_stats);
+            stats.rounds = stats.nodes.iter().map(|n| n.rounds).max().unwrap_or(0);
+            
+            Ok((outputs, client_output, stats))
+        })
+    }
+}
+
+// One party's share of an authenticated Beaver triple (random a, b and
+// c = a·b), consumed by one multiplication
+#[derive(Debug, Clone)]
+pub struct BeaverTriple {
+    pub a: AuthShare,
+    pub b: AuthShare,
+    pub c: AuthShare,
+}
+
+// Random authenticated mask [r]; r is given to the input owner only
+#[derive(Debug, Clone)]
+pub struct InputMask {
+    pub value: Fp,
+    pub sharing: AuthSharing,
+}
+
+// Preprocessing phase: a simulated trusted dealer picks every node's MAC
+// key and hands out authenticated triples and input masks before any inputs
+// are known
+pub struct TrustedDealer {
+    threshold: usize,
+    total: usize,
+    mac_keys: Vec<Fp>, // alpha per node: parties, then client
+}
+
+impl TrustedDealer {
+    pub fn new(threshold: usize, total: usize) -> Self {
+        Self {
+            threshold,
+            total,
+            mac_keys: (0..=total).map(|_| Fp::random()).collect(),
+        }
+    }
+    
+    pub fn client_mac_key(&self) -> Fp {
+        self.mac_keys[self.total]
+    }
+    
+    // Shamir-share a secret and MAC every share towards every other node
+    pub fn authenticate(&self, secret: Fp) -> AuthSharing {
+        let sharing = SharedValue::share(secret, self.threshold, self.total);
+        let nodes = self.total + 1;
+        // verifier_keys[j][i]: node j's key for party i's share
+        let verifier_keys: Vec<Vec<Fp>> = (0..nodes)
+            .map(|j| (0..self.total).map(|i| if i == j { Fp::ZERO } else { Fp::random() }).collect())
+            .collect();
+        
+        let shares = sharing.shares.iter()
+            .map(|share| {
+                let i = share.party;
+                AuthShare {
+                    value: share.value,
+                    tags: (0..nodes)
+                        .map(|j| if i == j { Fp::ZERO } else { self.mac_keys[j] * share.value + verifier_keys[j][i] })
+                        .collect(),
+                    keys: verifier_keys[i].clone(),
+                }
+            })
+            .collect();
+        
+        AuthSharing {
+            threshold: self.threshold,
+            shares,
+            client_keys: verifier_keys[self.total].clone(),
+        }
+    }
+    
+    pub fn input_mask(&self) -> InputMask {
+        let value = Fp::random();
+        InputMask { value, sharing: self.authenticate(value) }
+    }
+    
+    // Returns each party's list of triple shares, indexed by party
+    pub fn generate_triples(&self, count: usize) -> Vec<Vec<BeaverTriple>> {
+        let mut per_party = vec![Vec::with_capacity(count); self.total];
+        for _ in 0..count {
+            let a = Fp::random();
+            let b = Fp::random();
+            let a_shares = self.authenticate(a).shares;
+            let b_shares = self.authenticate(b).shares;
+            let c_shares = self.authenticate(a * b).shares;
+            for (party, triples) in per_party.iter_mut().enumerate() {
+                triples.push(BeaverTriple {
+                    a: a_shares[party].clone(),
+                    b: b_shares[party].clone(),
+                    c: c_shares[party].clone(),
+                });
+            }
+        }
+        per_party
+    }
+}
+
+// Long-term identities of the compute parties: an ML-DSA key to sign the
+// transitions they executed and a local key their stored share is sealed under
+pub struct MpcCommittee {
+    threshold: usize,
+    signing_keys: Vec<KeyPair<MlDsa65>>,
+    storage_keys: Vec<[u8; 32]>,
+}
+
+impl MpcCommittee {
+    pub fn new(threshold: usize, parties: usize) -> Self {
+        let mut rng = rand::thread_rng();
+        Self {
+            threshold,
+            signing_keys: (0..parties).map(|_| MlDsa65::key_gen(&mut rng)).collect(),
+            storage_keys: (0..parties)
+                .map(|_| {
+                    let mut key = [0u8; 32];
+                    rng.fill(&mut key);
+                    key
+                })
+                .collect(),
+        }
+    }
+    
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+    
+    pub fn verifying_keys(&self) -> Vec<VerifyingKey<MlDsa65>> {
+        self.signing_keys.iter().map(|pair| pair.verifying_key().clone()).collect()
+    }
+    
+    // On-chain state of a shared contract: every party's authenticated share,
+    // sealed under that party's own storage key
+    pub fn seal_shares(&self, contract_id: &str, shares: &[AuthShare]) -> EncryptedState {
+        let ciphertext = shares
+            .iter()
+            .zip(&self.storage_keys)
+            .flat_map(|(share, key)| {
+                let bytes: Vec<u8> = std::iter::once(share.value)
+                    .chain(share.tags.iter().copied())
+                    .chain(share.keys.iter().copied())
+                    .flat_map(|x| x.value().to_le_bytes())
+                    .collect();
+                aead_seal(key, &bytes, contract_id.as_bytes())
+            })
+            .collect();
+        EncryptedState::from_ciphertext(ciphertext)
+    }
+    
+    pub fn sign_transition(&self, party: usize, contract_id: &str, previous: &[u8; 32], new: &[u8; 32], call: &[u8; 32]) -> Signature<MlDsa65> {
+        self.signing_keys[party].signing_key().sign(&transition_payload(contract_id, previous, new, call))
+    }
+}
+
+// Token ledger whose balances live only as shares. Transfers are share-local
+// (sub from sender, add to receiver); balances are revealed on request only.
+pub struct MpcTokenLedger {
+    balances: Vec<SharedValue>,
+    threshold: usize,
+    total: usize,
+}
+
+impl MpcTokenLedger {
+    pub fn new(initial_balances: &[u64], threshold: usize, total: usize) -> Self {
+        let balances = initial_balances.iter()
+            .map(|b| SharedValue::share(*b, threshold, total))
+            .collect();
+        Self { balances, threshold, total }
+    }
+    
+    // Sender shares the secret amount with the parties
+    pub fn share_amount(&self, amount: u64) -> SharedValue {
+        SharedValue::share(amount, self.threshold, self.total)
+    }
+    
+    // NOTE: no overdraft check - that needs a secure comparison, not a linear op
+    pub fn transfer(&mut self, from: usize, to: usize, amount: &SharedValue) {
+        self.balances[from] = self.balances[from].sub(amount);
+        self.balances[to] = self.balances[to].add(amount);
+    }
+    
+    pub fn reveal_balance(&self, account: usize) -> Option<u64> {
+        self.balances.get(account)?.reconstruct()
+    }
+    
+    // Same transfer on unencrypted balances, for the baseline
+    pub fn plaintext_transfer(balances: &mut [u64], from: usize, to: usize, amount: u64) {
+        balances[from] -= amount;
+        balances[to] += amount;
+    }
+}
+
+// Bids are compared bit by bit, so they must fit in this many bits
+pub const AUCTION_BID_BITS: usize = 16;
+
+// Sealed-bid first-price auction: bidders input the bits of their bid, the
+// parties find the highest bid with Beaver-triple comparisons and open only
+// the winner and the winning price. Ties go to the earliest bid.
+pub struct SealedBidAuction {
+    // Held by each bidder's client until the input phase
+    bids: Vec<u64>,
+    threshold: usize,
+    total: usize,
+    network: NetworkConfig,
+    faults: Vec<(usize, MpcFault)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuctionOutcome {
+    pub winner: usize,
+    pub winning_bid: u64,
+}
+
+impl SealedBidAuction {
+    pub fn new(threshold: usize, total: usize) -> Self {
+        Self {
+            bids: Vec::new(),
+            threshold,
+            total,
+            network: NetworkConfig::lan(),
+            faults: Vec::new(),
+        }
+    }
+    
+    pub fn with_network(mut self, config: NetworkConfig) -> Self {
+        self.network = config;
+        self
+    }
+    
+    // Fault-injection mode for the MPC parties
+    pub fn with_faults(mut self, faults: &[(usize, MpcFault)]) -> Self {
+        self.faults = faults.to_vec();
+        self
+    }
+    
+    pub fn submit_bid(&mut self, amount: u64) -> Result<usize, MpcError> {
+        if amount >= 1 << AUCTION_BID_BITS {
+            return Err(MpcError::InputOutOfRange { value: amount, bits: AUCTION_BID_BITS });
+        }
+        self.bids.push(amount);
+        Ok(self.bids.len() - 1)
+    }
+    
+    // Per challenger: one comparison (2 per bit, minus 1), one select per
+    // bit and one select for the winner index
+    pub fn triples_needed(&self) -> usize {
+        self.bids.len().saturating_sub(1) * (3 * AUCTION_BID_BITS)
+    }
+    
+    // Same selection on the plain bids: highest bid, ties to the earliest
+    pub fn plaintext_outcome(&self) -> Option<AuctionOutcome> {
+        let (winner, &winning_bid) = self.bids
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, &bid)| bid)?;
+        Some(AuctionOutcome { winner, winning_bid })
+    }
+    
+    // Aborts with MpcError::MacCheckFailed naming the cheater instead of
+    // returning a wrong outcome
+    pub fn run_mpc(&self) -> (Result<AuctionOutcome, MpcError>, ExecutionResult) {
+        let ((outcome, stats), timings) = PhaseTimings::record(|| self.run_phases());
+        let error = outcome.as_ref().err().cloned().map(ExecutionError::from);
+        let result = ExecutionResult::from_phases(ExecutionModel::MPCBased, outcome.is_ok(), timings, 0, 0, Some(stats));
+        (outcome, result.with_error(error.as_ref()))
+    }
+    
+    fn run_phases(&self) -> (Result<AuctionOutcome, MpcError>, NetworkStats) {
+        // Offline: dealer generates every triple the circuit will consume and
+        // one authenticated input mask per bid bit
+        let (dealer, triples, masks) = Phase::Preprocess.span().in_scope(|| {
+            let dealer = TrustedDealer::new(self.threshold, self.total);
+            let triples = dealer.generate_triples(self.triples_needed());
+            let masks: Vec<InputMask> = (0..self.bids.len() * AUCTION_BID_BITS)
+                .map(|_| dealer.input_mask())
+                .collect();
+            (dealer, triples, masks)
+        });
+        
+        // Online: bidders broadcast their masked bits (LSB first), each party
+        // runs the running-maximum circuit on its shares, then opens only the
+        // outputs: winner and price (sum of 2^k · bit_k)
+        let compute_phase = Phase::Compute.span().entered();
+        let network = MpcNetwork::new(self.network, &dealer).with_faults(&self.faults);
+        let run = network.run(
+            |ctx| {
+                let mut triples = triples[ctx.id].clone();
+                let own_masks: Vec<AuthShare> = masks.iter()
+                    .map(|m| m.sharing.shares[ctx.id].clone())
+                    .collect();
+                let inputs = ctx.receive_masked_inputs(&own_masks)?;
+                let bids: Vec<&[AuthShare]> = inputs.chunks(AUCTION_BID_BITS).collect();
+                let first = bids.first().ok_or(MpcError::NotShared)?;
+                
+                let mut best_bits = first.to_vec();
+                let mut best_index = ctx.constant(Fp::ZERO);
+                for (i, bid_bits) in bids.iter().enumerate().skip(1) {
+                    let higher = ctx.greater_than(bid_bits, &best_bits, &mut triples)?;
+                    let mut pairs: Vec<(AuthShare, AuthShare)> = bid_bits.iter().cloned().zip(best_bits).collect();
+                    pairs.push((ctx.constant(Fp::new(i as u64)), best_index));
+                    let mut selected = ctx.select_many(&higher, &pairs, &mut triples)?;
+                    best_index = selected.pop().ok_or(MpcError::NotShared)?;
+                    best_bits = selected;
+                }
+                
+                let price = best_bits.iter().enumerate()
+                    .fold(AuthShare::zero(ctx.parties), |acc, (k, bit)| acc.add(&bit.scale(Fp::new(1u64 << k))));
+                let opened = ctx.open_many(&[best_index, price])?;
+                ctx.check_macs()?;
+                Ok(AuctionOutcome { winner: opened[0].value() as usize, winning_bid: opened[1].value() })
+            },
+            |ctx| {
+                ctx.next_round();
+                let masked: Vec<Fp> = self.bids.iter()
+                    .flat_map(|bid| (0..AUCTION_BID_BITS).map(move |k| Fp::new((bid >> k) & 1)))
+                    .zip(&masks)
+                    .map(|(bit, mask)| bit - mask.value)
+                    .collect();
+                for party in 0..ctx.parties {
+                    ctx.send(party, masked.clone());
+                }
+            },
+        );
+        // Freeing the preprocessed material is part of the run; left to the
+        // end of the function it would fall outside every phase
+        drop((network, triples, masks, dealer));
+        drop(compute_phase);
+        let (outputs, _, stats) = match run {
+            Ok(run) => run,
+            Err(error) => return (Err(error), NetworkStats::default()),
+        };
+        
+        // Any failed MAC check aborts the whole execution
+        let outcome = Phase::Reconstruct.span().in_scope(|| {
+            outputs.into_iter()
+                .collect::<Result<Vec<_>, _>>()
+                .and_then(|outcomes| outcomes.into_iter().next().ok_or(MpcError::NotShared))
+        });
+        (outcome, stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EncryptedCounter, InjectedFault};
+    use crate::tests::KEY;
+    
+    // MPC increments and transfers run on shares only; the owner reveals
+    #[test]
+    fn mpc_counter_increments_on_shares() {
+        let mut counter = EncryptedCounter::new(41, KEY);
+        for _ in 0..2 {
+            counter.increment(ExecutionModel::MPCBased, KEY).expect("MPC increment");
+        }
+        assert_eq!(counter.reveal_mpc(KEY), Ok(43));
+    }
+    
+    #[test]
+    fn mpc_ledger_transfer_matches_plaintext() {
+        let mut ledger = MpcTokenLedger::new(&[100, 50], 3, 5);
+        let amount = ledger.share_amount(30);
+        ledger.transfer(0, 1, &amount);
+        let mut plain_balances = [100, 50];
+        MpcTokenLedger::plaintext_transfer(&mut plain_balances, 0, 1, 30);
+        assert_eq!(ledger.reveal_balance(0), Some(plain_balances[0]));
+        assert_eq!(ledger.reveal_balance(1), Some(plain_balances[1]));
+    }
+    
+    #[test]
+    fn auction_winner_matches_plaintext_maximum() {
+        let mut auction = SealedBidAuction::new(3, 5);
+        for bid in [120, 340, 275, 90] {
+            auction.submit_bid(bid).expect("bid in range");
+        }
+        let (outcome, _) = auction.run_mpc();
+        assert_eq!(outcome.ok(), auction.plaintext_outcome());
+    }
+    
+    // A deviating party fails the MAC check and is named in the abort
+    #[test]
+    fn auction_aborts_naming_wrong_product_party() {
+        let mut auction = SealedBidAuction::new(3, 5).with_faults(&[(2, MpcFault::WrongProduct)]);
+        for bid in [120, 340, 275, 90] {
+            auction.submit_bid(bid).expect("bid in range");
+        }
+        let (outcome, _) = auction.run_mpc();
+        assert!(matches!(outcome, Err(MpcError::MacCheckFailed { cheating_party: 2, .. })), "{:?}", outcome);
+    }
+    
+    #[test]
+    fn counter_reveal_aborts_naming_wrong_share_party() {
+        let mut counter = EncryptedCounter::new(41, KEY).with_mpc_faults(&[(4, MpcFault::WrongShare)]);
+        // The increment opens nothing, so only the reveal sees the bad share
+        counter.increment(ExecutionModel::MPCBased, KEY).expect("MPC increment");
+        let revealed = counter.reveal_mpc(KEY);
+        assert!(matches!(revealed, Err(MpcError::MacCheckFailed { cheating_party: 4, .. })), "{:?}", revealed);
+    }
+    
+    // Parties that lost their shares cannot run the next increment
+    #[test]
+    fn lost_shares_reported_against_the_threshold() {
+        let mut counter = EncryptedCounter::new(41, KEY);
+        counter.increment(ExecutionModel::MPCBased, KEY).expect("MPC increment");
+        let mut snapshot = counter.snapshot();
+        snapshot.mpc_shares.as_mut().expect("shared").shares.truncate(2);
+        let (result, _) = counter.execute(ExecutionModel::MPCBased, &snapshot, KEY);
+        assert_eq!(result.error, Some(ExecutionError::InsufficientShares { held: 2, needed: 3 }));
+    }
+    
+    // Dropped parties stop before they run; the client only finds out by
+    // timing out on their acknowledgements
+    #[test]
+    fn party_dropout_detected_by_timeout() {
+        let counter = EncryptedCounter::new(41, KEY);
+        let dropout = Some(InjectedFault::PartyDropout);
+        let (result, next) = counter.execute_with_fault(ExecutionModel::MPCBased, &counter.snapshot(), KEY, dropout);
+        assert!(next.is_err());
+        assert_eq!(result.error, Some(ExecutionError::InsufficientShares { held: 2, needed: 3 }));
+        let stats = result.network_stats.expect("network ran");
+        let dropped: Vec<_> = stats.nodes.iter().filter(|node| node.dropped_out).collect();
+        assert_eq!(dropped.len(), 3);
+        assert!(dropped.iter().all(|node| node.messages_sent == 0), "dropped parties sent nothing");
+        let timeout = NetworkConfig::lan().peer_timeout.as_micros() as u64;
+        assert!(stats.detection_us() > 0, "the client waited on the dropped parties");
+        assert!(result.total_time_us >= timeout, "the call lasted the whole timeout");
+    }
+    
+    // Opening interpolates from whichever parties answer within the timeout
+    #[test]
+    fn open_tolerates_dropouts_down_to_the_threshold() {
+        let dealer = TrustedDealer::new(3, 5);
+        let open = |dropouts: &[usize]| {
+            let network = MpcNetwork::new(NetworkConfig::lan(), &dealer).with_dropouts(dropouts);
+            let (outputs, _, _) = network.run(|ctx| ctx.open_many(&[ctx.constant(Fp::new(7))]), |_| ()).expect("no node panicked");
+            outputs
+        };
+        assert_eq!(open(&[3, 4]), vec![Ok(vec![Fp::new(7)]); 3]);
+        assert_eq!(open(&[2, 3, 4]), vec![Err(MpcError::InsufficientShares { held: 2, needed: 3 }); 2]);
+    }
+    
+    #[test]
+    fn oversized_bid_rejected() {
+        assert!(SealedBidAuction::new(3, 5).submit_bid(1 << AUCTION_BID_BITS).is_err());
+    }
+    
+    // Bad inputs, short preprocessing and a vanished peer are errors, not
+    // panics. One party, so the client is its only peer.
+    #[test]
+    fn mpc_protocol_failures_are_errors() {
+        let dealer = TrustedDealer::new(1, 1);
+        let solo = MpcNetwork::new(NetworkConfig::lan(), &dealer);
+        let node_errors = |run: Result<(Vec<Option<MpcError>>, (), NetworkStats), MpcError>| run.ok().map(|(outputs, _, _)| outputs);
+        let starved = solo.run(|ctx| ctx.mul(&ctx.constant(Fp::ONE), &ctx.constant(Fp::ONE), &mut Vec::new()).err(), |_| ());
+        assert_eq!(node_errors(starved), Some(vec![Some(MpcError::OutOfTriples)]), "too few Beaver triples");
+        let abandoned = solo.run(|ctx| ctx.recv(ctx.client_id()).err(), |_| ());
+        assert_eq!(node_errors(abandoned), Some(vec![Some(MpcError::Disconnected { from: 1 })]), "peer gone mid-protocol");
+        let malformed = solo.run(|ctx| ctx.receive_masked_inputs(&[ctx.constant(Fp::ZERO)]).err(), |ctx| ctx.send(0, Vec::new()));
+        assert_eq!(node_errors(malformed), Some(vec![Some(MpcError::MalformedMessage { from: 1 })]), "short input message");
+    }
+}
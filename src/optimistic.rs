@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use crate::{
+    COUNTER_CONTRACT_ID, COUNTER_SLOT, CounterSnapshot, EncryptedCounter, ExecutionError,
+    ExecutionEvidence, ExecutionModel, Rejection, StateCommitment, TransitionValidator,
+};
+use crate::keys::EncryptedEvent;
+use crate::tee::Enclave;
+
+// Optimistic TEE mode: the executor posts a transition with a bond and it
+// is accepted at once, on the enclave's signature alone. It becomes final
+// when the challenge window closes. Until then any verifier can challenge;
+// after a dispute period in which the executor may answer, a referee
+// enclave re-executes the transition from the old state and the published
+// inputs, and whichever side was wrong loses its bond.
+#[derive(Debug, Clone, Copy)]
+pub struct OptimisticConfig {
+    pub window: Duration,
+    pub dispute_period: Duration,
+    pub bond: u64,
+}
+
+impl Default for OptimisticConfig {
+    fn default() -> Self {
+        Self {
+            window: Duration::from_secs(600),
+            dispute_period: Duration::from_secs(120),
+            bond: 1_000,
+        }
+    }
+}
+
+impl OptimisticConfig {
+    pub fn with_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifierBehavior {
+    Honest,   // re-executes everything, challenges on a mismatch at once
+    Silent,   // colludes with the executor: never challenges
+    Griefing, // challenges every transition as late as it can
+}
+
+// Who holds a balance in the bond ledger
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BondAccount {
+    Executor,
+    Verifier(usize),
+}
+
+pub struct OptimisticVerifier {
+    pub behavior: VerifierBehavior,
+    enclave: Enclave,
+}
+
+// Posted on-chain: everything a challenger needs to replay the call
+pub struct PostedTransition {
+    pub previous: StateCommitment,
+    pub next: CounterSnapshot,
+    pub call: EncryptedEvent,        // the call it claims to execute
+    pub evidence: ExecutionEvidence,
+    pub bond: u64,
+    pub execution: Duration,         // executor's time to produce it
+}
+
+// How a posted transition ended, and how long after posting. Time is
+// simulated for windows and dispute periods, measured for re-execution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Settlement {
+    Final { after: Duration },                      // unchallenged
+    Upheld { after: Duration, challenger: usize },  // challenger's bond to the executor
+    Reverted { after: Duration, challenger: usize }, // executor's bond to the challenger
+}
+
+impl Settlement {
+    // Simulated: windows and dispute periods are added, never waited out
+    pub fn time_to_finality(&self) -> Duration {
+        match self {
+            Settlement::Final { after } | Settlement::Upheld { after, .. } | Settlement::Reverted { after, .. } => *after,
+        }
+    }
+}
+
+pub struct OptimisticTee {
+    counter: EncryptedCounter,
+    config: OptimisticConfig,
+    validator: TransitionValidator,
+    head: CounterSnapshot,
+    verifiers: Vec<OptimisticVerifier>,
+    referee: Enclave,
+    pending: Option<EncryptedEvent>, // call the user requested, not yet applied
+    balances: HashMap<BondAccount, u64>, // free to bond; escrowed bonds are out of it
+}
+
+impl OptimisticTee {
+    // Every verifier and the referee get their own attested enclave holding
+    // the contract key
+    pub fn new(
+        mut counter: EncryptedCounter,
+        config: OptimisticConfig,
+        behaviors: &[VerifierBehavior],
+        key: &[u8],
+    ) -> Result<Self, ExecutionError> {
+        counter.prepare(ExecutionModel::TEEBased, key)?;
+        let launch = || counter.attested_enclave(key).ok_or(ExecutionError::AttestationFailed);
+        let verifiers = behaviors
+            .iter()
+            .map(|&behavior| Ok(OptimisticVerifier { behavior, enclave: launch()? }))
+            .collect::<Result<Vec<_>, ExecutionError>>()?;
+        let referee = launch()?;
+        Ok(Self {
+            validator: counter.validator(),
+            head: counter.snapshot(),
+            counter,
+            config,
+            verifiers,
+            referee,
+            pending: None,
+            balances: HashMap::new(),
+        })
+    }
+    
+    pub fn deposit(&mut self, account: BondAccount, amount: u64) {
+        *self.balances.entry(account).or_default() += amount;
+    }
+    
+    pub fn balance(&self, account: BondAccount) -> u64 {
+        self.balances.get(&account).copied().unwrap_or(0)
+    }
+    
+    // Moves `amount` out of `account` into escrow
+    fn escrow(&mut self, account: BondAccount, amount: u64) -> Result<(), Rejection> {
+        let held = self.balance(account);
+        if held < amount {
+            return Err(Rejection::InsufficientBond { held, needed: amount });
+        }
+        self.balances.insert(account, held - amount);
+        Ok(())
+    }
+    
+    pub fn head(&self) -> StateCommitment {
+        self.head.commitment()
+    }
+    
+    // The user seals the call and queues it on-chain; the commitment is what
+    // any posted transition must be signed over
+    pub fn request(&mut self, key: &[u8]) -> Result<[u8; 32], ExecutionError> {
+        let call = self.counter.seal_call(key).ok_or(ExecutionError::DecryptFailed)?;
+        Ok(self.pending.insert(call).commitment())
+    }
+    
+    // Executor runs the requested call in its enclave and posts the result.
+    // With `forged`, a breached enclave signs that value instead, over a call
+    // of its own whose arguments account for it.
+    pub fn post(&self, key: &[u8], forged: Option<u64>) -> Result<PostedTransition, ExecutionError> {
+        let start = Instant::now();
+        let requested = self.pending.clone().ok_or(ExecutionError::NoPendingCall)?;
+        let (next, evidence, call) = match forged {
+            None => {
+                let (next, evidence) = self
+                    .counter
+                    .execute_call(ExecutionModel::TEEBased, &self.head, key, Some(requested.clone()), None)
+                    .1?;
+                (next, evidence, requested)
+            }
+            Some(value) => {
+                let enclave = self.counter.enclave.as_ref().ok_or(ExecutionError::AttestationFailed)?;
+                let (state, call, signature) = enclave
+                    .sign_forged_transition(COUNTER_CONTRACT_ID, self.head.state(), value)
+                    .ok_or(ExecutionError::AttestationFailed)?;
+                let mut store = self.head.store.fork();
+                store.write(COUNTER_SLOT, state);
+                let report = enclave.attest(self.head.state().commitment);
+                let evidence = ExecutionEvidence::Enclave { report, signature, call: call.commitment() };
+                (CounterSnapshot { store, ..self.head.clone() }, evidence, call)
+            }
+        };
+        Ok(PostedTransition {
+            previous: self.head(),
+            next,
+            call,
+            evidence,
+            bond: self.config.bond,
+            execution: start.elapsed(),
+        })
+    }
+    
+    // Accept on the evidence alone with the executor's bond in escrow, let
+    // every verifier look at it, settle the earliest challenge if any, pay
+    // out the bonds, and move the head unless reverted. A challenge that
+    // cannot be raised hands the executor's bond back.
+    pub fn submit(&mut self, posted: PostedTransition) -> Result<Settlement, Rejection> {
+        if posted.previous != self.head() {
+            return Err(Rejection::StalePrevious);
+        }
+        if posted.bond < self.config.bond {
+            return Err(Rejection::InsufficientBond { held: posted.bond, needed: self.config.bond });
+        }
+        self.validator.validate(&posted.previous, posted.next.state(), &posted.evidence)?;
+        self.escrow(BondAccount::Executor, posted.bond)?;
+        
+        // Only a verifier that can put up the bond can challenge
+        let late = self.config.window.mul_f64(0.99);
+        let challenge = self.verifiers
+            .iter()
+            .enumerate()
+            .filter(|&(index, _)| self.balance(BondAccount::Verifier(index)) >= self.config.bond)
+            .filter_map(|(index, verifier)| {
+                let start = Instant::now();
+                let valid = self.replay(&verifier.enclave, &posted);
+                match verifier.behavior {
+                    VerifierBehavior::Honest if !valid => Some((start.elapsed(), index)),
+                    VerifierBehavior::Griefing => Some((late, index)),
+                    _ => None,
+                }
+            })
+            .min_by_key(|&(at, _)| at);
+        let settlement = match challenge {
+            Some((at, challenger)) => self.challenge(&posted, challenger, at),
+            None => Ok(Settlement::Final { after: self.config.window }),
+        };
+        let settlement = match settlement {
+            Ok(settlement) => settlement,
+            Err(rejection) => {
+                self.deposit(BondAccount::Executor, posted.bond);
+                return Err(rejection);
+            }
+        };
+        match settlement {
+            Settlement::Final { .. } => self.deposit(BondAccount::Executor, posted.bond),
+            Settlement::Upheld { .. } => self.deposit(BondAccount::Executor, posted.bond + self.config.bond),
+            Settlement::Reverted { challenger, .. } => {
+                self.deposit(BondAccount::Verifier(challenger), self.config.bond + posted.bond)
+            }
+        }
+        if !matches!(settlement, Settlement::Reverted { .. }) {
+            self.head = posted.next;
+            self.pending = None;
+        }
+        Ok(settlement)
+    }
+    
+    // Verifier `challenger` escrows its bond and disputes `posted` at `at`
+    // after posting. The referee re-executes once the dispute period is
+    // over; submit pays out the bonds.
+    fn challenge(&mut self, posted: &PostedTransition, challenger: usize, at: Duration) -> Result<Settlement, Rejection> {
+        if at > self.config.window {
+            return Err(Rejection::WindowClosed);
+        }
+        self.escrow(BondAccount::Verifier(challenger), self.config.bond)?;
+        let start = Instant::now();
+        let valid = self.replay(&self.referee, posted);
+        let resolved = at + self.config.dispute_period + start.elapsed();
+        Ok(if valid {
+            // A failed challenge cannot shorten the window
+            Settlement::Upheld { after: resolved.max(self.config.window), challenger }
+        } else {
+            Settlement::Reverted { after: resolved, challenger }
+        })
+    }
+    
+    // Valid only for the requested call, signed over and replayed as posted
+    fn replay(&self, enclave: &Enclave, posted: &PostedTransition) -> bool {
+        let Some(authorised) = self.pending.as_ref().map(EncryptedEvent::commitment) else {
+            return false;
+        };
+        let signed = matches!(posted.evidence, ExecutionEvidence::Enclave { call, .. } if call == authorised);
+        signed
+            && enclave.check_transition(
+                COUNTER_CONTRACT_ID,
+                COUNTER_SLOT,
+                self.head.state(),
+                &posted.call,
+                &authorised,
+                posted.next.state(),
+            ) == Ok(true)
+    }
+    
+    pub fn reveal(&self, key: &[u8]) -> Option<u64> {
+        self.counter.decrypt_value(self.head.state(), key).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::KEY;
+    
+    // Executor and verifiers each start with one bond
+    fn funded(config: OptimisticConfig, behaviors: &[VerifierBehavior]) -> OptimisticTee {
+        let mut tee = OptimisticTee::new(EncryptedCounter::new(41, KEY), config, behaviors, KEY).expect("optimistic TEE");
+        tee.deposit(BondAccount::Executor, config.bond);
+        (0..behaviors.len()).for_each(|verifier| tee.deposit(BondAccount::Verifier(verifier), config.bond));
+        tee
+    }
+    
+    // Settlement, revealed value and balances afterwards, executor first
+    fn settle(behaviors: &[VerifierBehavior], forged: Option<u64>) -> (Settlement, Option<u64>, Vec<u64>) {
+        let mut tee = funded(OptimisticConfig::default(), behaviors);
+        tee.request(KEY).expect("call requested");
+        let posted = tee.post(KEY, forged).expect("posted");
+        let settlement = tee.submit(posted).expect("settled");
+        let accounts = std::iter::once(BondAccount::Executor).chain((0..behaviors.len()).map(BondAccount::Verifier));
+        (settlement, tee.reveal(KEY), accounts.map(|account| tee.balance(account)).collect())
+    }
+    
+    #[test]
+    fn unchallenged_transition_final_when_the_window_closes() {
+        let config = OptimisticConfig::default();
+        let (settlement, value, bonds) = settle(&[VerifierBehavior::Honest; 3], None);
+        assert_eq!(settlement, Settlement::Final { after: config.window });
+        assert_eq!(value, Some(42));
+        assert_eq!(bonds, [config.bond; 4], "the executor's bond comes back");
+    }
+    
+    #[test]
+    fn griefing_challenge_upheld_and_its_bond_paid_to_the_executor() {
+        let config = OptimisticConfig::default();
+        let (settlement, value, bonds) = settle(&[VerifierBehavior::Honest, VerifierBehavior::Griefing], None);
+        assert!(
+            matches!(settlement, Settlement::Upheld { after, challenger: 1 } if after > config.window),
+            "{:?}",
+            settlement
+        );
+        assert_eq!(value, Some(42));
+        assert_eq!(bonds, [2 * config.bond, config.bond, 0]);
+    }
+    
+    // One honest verifier among colluders, in every position
+    #[test]
+    fn forged_transition_reverted_by_the_single_honest_verifier() {
+        let config = OptimisticConfig::default();
+        let verifiers = 5;
+        for trial in 0..2 * verifiers {
+            let mut behaviors = vec![VerifierBehavior::Silent; verifiers];
+            behaviors[trial % verifiers] = VerifierBehavior::Honest;
+            let (settlement, value, _) = settle(&behaviors, Some(43 + trial as u64));
+            assert!(
+                matches!(settlement, Settlement::Reverted { challenger, .. } if challenger == trial % verifiers),
+                "trial {}: {:?}",
+                trial,
+                settlement
+            );
+            assert_eq!(value, Some(41), "trial {}", trial);
+            let finality = settlement.time_to_finality();
+            assert!(finality >= config.dispute_period && finality < config.window, "trial {}: {:?}", trial, finality);
+        }
+    }
+    
+    // 41 -> 100 posted with a call of args [59]: it replays, but is not the
+    // user's
+    #[test]
+    fn forged_call_reverted_and_the_executor_bond_paid_to_the_challenger() {
+        let config = OptimisticConfig::default();
+        let (settlement, value, bonds) = settle(&[VerifierBehavior::Honest], Some(100));
+        assert!(matches!(settlement, Settlement::Reverted { challenger: 0, .. }), "{:?}", settlement);
+        assert_eq!(value, Some(41));
+        assert_eq!(bonds, [0, 2 * config.bond]);
+    }
+    
+    #[test]
+    fn forged_state_finalizes_without_an_honest_verifier() {
+        let config = OptimisticConfig::default();
+        let (settlement, value, _) = settle(&[VerifierBehavior::Silent; 5], Some(100));
+        assert_eq!(settlement, Settlement::Final { after: config.window });
+        assert_eq!(value, Some(100));
+    }
+    
+    #[test]
+    fn challenge_after_the_window_rejected() {
+        let config = OptimisticConfig::default();
+        let mut tee = funded(config, &[VerifierBehavior::Honest]);
+        tee.request(KEY).expect("call requested");
+        let posted = tee.post(KEY, Some(100)).expect("posted");
+        assert!(matches!(
+            tee.challenge(&posted, 0, config.window + Duration::from_secs(1)),
+            Err(Rejection::WindowClosed)
+        ));
+    }
+    
+    // An honest challenge that misses a zero-length window is refused, and
+    // the executor's escrowed bond comes back
+    #[test]
+    fn refused_challenge_refunds_the_executor_bond() {
+        let config = OptimisticConfig::default().with_window(Duration::ZERO);
+        let mut tee = funded(config, &[VerifierBehavior::Honest]);
+        tee.request(KEY).expect("call requested");
+        let posted = tee.post(KEY, Some(100)).expect("posted");
+        assert_eq!(tee.submit(posted), Err(Rejection::WindowClosed));
+        assert_eq!(tee.balance(BondAccount::Executor), config.bond);
+        assert_eq!(tee.balance(BondAccount::Verifier(0)), config.bond);
+    }
+    
+    #[test]
+    fn nothing_to_post_before_a_call_is_requested() {
+        let tee = funded(OptimisticConfig::default(), &[VerifierBehavior::Honest]);
+        assert!(matches!(tee.post(KEY, None), Err(ExecutionError::NoPendingCall)));
+    }
+    
+    #[test]
+    fn executor_without_a_bond_cannot_post() {
+        let config = OptimisticConfig::default();
+        let mut tee = OptimisticTee::new(EncryptedCounter::new(41, KEY), config, &[VerifierBehavior::Honest], KEY)
+            .expect("optimistic TEE");
+        tee.request(KEY).expect("call requested");
+        let posted = tee.post(KEY, None).expect("posted");
+        assert_eq!(tee.submit(posted), Err(Rejection::InsufficientBond { held: 0, needed: config.bond }));
+    }
+    
+    #[test]
+    fn window_is_configurable() {
+        let window = Duration::from_secs(60);
+        let mut tee = funded(OptimisticConfig::default().with_window(window), &[VerifierBehavior::Honest]);
+        tee.request(KEY).expect("call requested");
+        let posted = tee.post(KEY, None).expect("posted");
+        assert_eq!(tee.submit(posted), Ok(Settlement::Final { after: window }));
+    }
+}
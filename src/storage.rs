@@ -0,0 +1,743 @@
+use sha3::{Shake256, digest::{Update, ExtendableOutput, XofReader}};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use winterfell::math::fields::f128::BaseElement;
+use crate::keys::{KeyPath, ViewingKey, aead_open, aead_seal, derive_key};
+use crate::tee::KeyRotation;
+
+// Encrypted contract state. AES-256-GCM under a key for `key_path`, which is
+// bound as associated data so a state cannot be passed off as another slot's.
+// The plaintext is padded before sealing (see PaddingPolicy).
+#[derive(Clone)]
+pub struct EncryptedState {
+    pub ciphertext: Vec<u8>,
+    pub commitment: [u8; 32],
+    pub key_path: KeyPath,
+}
+
+// Nonce and tag added by aead_seal
+pub(crate) const AEAD_OVERHEAD: usize = 12 + 16;
+
+// How far a state plaintext is padded before sealing, so the ciphertext
+// length shows a bucket rather than the state size. Padding is ISO/IEC
+// 7816-4: a 0x80 marker, then zeros up to the bucket.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum PaddingPolicy {
+    #[default]
+    None,                // marker byte only: the length shows
+    PowerOfTwo,          // next power of two
+    Buckets(Vec<usize>), // smallest bucket that fits, then multiples of the largest
+                         // (zero-sized buckets are ignored; none left: no padding)
+}
+
+impl PaddingPolicy {
+    // 256B / 1KB / 4KB
+    pub fn fixed() -> Self {
+        PaddingPolicy::Buckets(vec![256, 1024, 4096])
+    }
+    
+    // Padded length for a plaintext of `len` bytes, marker included
+    pub fn padded_len(&self, len: usize) -> usize {
+        let framed = len + 1;
+        match self {
+            PaddingPolicy::None => framed,
+            PaddingPolicy::PowerOfTwo => framed.next_power_of_two(),
+            PaddingPolicy::Buckets(buckets) => {
+                let usable = buckets.iter().copied().filter(|&bucket| bucket > 0);
+                match (usable.clone().find(|&bucket| bucket >= framed), usable.max()) {
+                    (Some(bucket), _) => bucket,
+                    (None, Some(largest)) => framed.div_ceil(largest) * largest,
+                    (None, None) => framed,
+                }
+            }
+        }
+    }
+    
+    fn pad(&self, plaintext: &[u8]) -> Vec<u8> {
+        let mut padded = Vec::with_capacity(self.padded_len(plaintext.len()));
+        padded.extend_from_slice(plaintext);
+        padded.push(0x80);
+        padded.resize(self.padded_len(plaintext.len()), 0);
+        padded
+    }
+}
+
+// Strips 7816-4 padding. Scans the whole buffer without branching on its
+// bytes, so the time depends on the padded length only.
+fn unpad(mut padded: Vec<u8>) -> Option<Vec<u8>> {
+    let mut marker = 0;
+    for (i, &byte) in padded.iter().enumerate() {
+        let nonzero = (byte != 0) as usize;
+        marker = nonzero * i + (1 - nonzero) * marker;
+    }
+    if padded.get(marker) != Some(&0x80) {
+        return None;
+    }
+    padded.truncate(marker);
+    Some(padded)
+}
+
+impl EncryptedState {
+    // `key` is the raw key for the state's own position in the tree
+    pub fn new(plaintext: &[u8], key: &[u8]) -> Self {
+        Self::padded(plaintext, key, &PaddingPolicy::None)
+    }
+    
+    pub fn padded(plaintext: &[u8], key: &[u8], padding: &PaddingPolicy) -> Self {
+        Self::with_path(plaintext, key, KeyPath::master(), padding)
+    }
+    
+    // State at `path`, encrypted under the key derived for it from `viewing_key`
+    pub fn at(path: KeyPath, plaintext: &[u8], viewing_key: &ViewingKey) -> Option<Self> {
+        Self::at_padded(path, plaintext, viewing_key, &PaddingPolicy::None)
+    }
+    
+    pub fn at_padded(path: KeyPath, plaintext: &[u8], viewing_key: &ViewingKey, padding: &PaddingPolicy) -> Option<Self> {
+        Some(Self::under(&viewing_key.derive(&path)?, plaintext, padding))
+    }
+    
+    // State at `leaf`'s own position, encrypted under it
+    pub fn under(leaf: &ViewingKey, plaintext: &[u8], padding: &PaddingPolicy) -> Self {
+        Self::with_path(plaintext, &leaf.secret, leaf.path.clone(), padding)
+    }
+    
+    // New contents for this state's position, e.g. after a call
+    pub fn reseal(&self, plaintext: &[u8], viewing_key: &ViewingKey, padding: &PaddingPolicy) -> Option<Self> {
+        Self::at_padded(self.key_path.clone(), plaintext, viewing_key, padding)
+    }
+    
+    fn with_path(plaintext: &[u8], key: &[u8], key_path: KeyPath, padding: &PaddingPolicy) -> Self {
+        let ciphertext = aead_seal(&Self::aead_key(key), &padding.pad(plaintext), &key_path.encode());
+        let commitment = Self::commit(&ciphertext);
+        
+        Self {
+            ciphertext,
+            commitment,
+            key_path,
+        }
+    }
+    
+    fn aead_key(key: &[u8]) -> [u8; 32] {
+        derive_key(b"ecliptica/state", &[key])
+    }
+    
+    // State produced by a party that does not hold the contract key
+    pub fn from_ciphertext(ciphertext: Vec<u8>) -> Self {
+        let commitment = Self::commit(&ciphertext);
+        Self {
+            ciphertext,
+            commitment,
+            key_path: KeyPath::master(),
+        }
+    }
+    
+    // Fails on bad padding as well as on a bad key or tag
+    pub fn decrypt(&self, key: &[u8]) -> Option<Vec<u8>> {
+        unpad(aead_open(&Self::aead_key(key), &self.ciphertext, &self.key_path.encode())?)
+    }
+    
+    // Plaintext length after padding: all an observer learns of the state
+    pub fn padded_len(&self) -> usize {
+        self.ciphertext.len().saturating_sub(AEAD_OVERHEAD)
+    }
+    
+    // Re-encryptions of this state stay in its bucket (or grow past it)
+    pub fn same_bucket(&self) -> PaddingPolicy {
+        PaddingPolicy::Buckets(vec![self.padded_len()])
+    }
+    
+    // Key epoch the state was written under, recorded in its key path
+    pub fn key_epoch(&self) -> Option<u64> {
+        self.key_path.key_epoch()
+    }
+    
+    // Selective disclosure: opens only if the state lies in the key's subtree
+    pub fn open(&self, viewing_key: &ViewingKey) -> Option<Vec<u8>> {
+        self.decrypt(&viewing_key.derive(&self.key_path)?.secret)
+    }
+    
+    // Blinding for a value commitment to this state's plaintext: a PRF of
+    // the ciphertext under the state's own key, so whoever can open the
+    // state can also check it against the commitment
+    pub fn value_blinding(&self, viewing_key: &ViewingKey) -> Option<BaseElement> {
+        let leaf = viewing_key.derive(&self.key_path)?;
+        let bytes = derive_key(b"ecliptica/value-blinding", &[&leaf.secret, &self.commitment]);
+        Some(BaseElement::new(u128::from_le_bytes(bytes[..16].try_into().expect("16 bytes"))))
+    }
+    
+    pub fn commitment_is_valid(&self) -> bool {
+        Self::commit(&self.ciphertext) == self.commitment
+    }
+    
+    // Storage format: key path length, key path, ciphertext. The commitment
+    // is recomputed on load rather than trusted.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let path = self.key_path.encode();
+        [&(path.len() as u32).to_le_bytes()[..], &path, &self.ciphertext].concat()
+    }
+    
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let len = u32::from_le_bytes(bytes.get(..4)?.try_into().ok()?) as usize;
+        let key_path = KeyPath::decode(bytes.get(4..4 + len)?)?;
+        let ciphertext = bytes[4 + len..].to_vec();
+        Some(Self { commitment: Self::commit(&ciphertext), ciphertext, key_path })
+    }
+    
+    fn commit(data: &[u8]) -> [u8; 32] {
+        let mut hasher = Shake256::default();
+        hasher.update(data);
+        let mut output = [0u8; 32];
+        hasher.finalize_xof().read(&mut output);
+        output
+    }
+}
+
+// Encrypted key-value store: one EncryptedState per u64 slot under a sparse
+// SHAKE-256 Merkle tree of depth 64 (one level per slot bit). A leaf binds
+// the slot number to the state commitment; empty slots are all-zero leaves.
+pub const STORE_DEPTH: usize = 64;
+
+fn store_leaf(slot: u64, state: &EncryptedState) -> [u8; 32] {
+    derive_key(b"ecliptica/store/leaf", &[&slot.to_le_bytes(), &state.commitment])
+}
+
+fn store_node(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    derive_key(b"ecliptica/store/node", &[left, right])
+}
+
+// Root of an all-empty subtree, by height
+fn empty_subtrees() -> &'static [[u8; 32]] {
+    static EMPTY: OnceLock<Vec<[u8; 32]>> = OnceLock::new();
+    EMPTY.get_or_init(|| {
+        let mut empty = vec![[0u8; 32]];
+        for level in 0..STORE_DEPTH {
+            empty.push(store_node(&empty[level], &empty[level]));
+        }
+        empty
+    })
+}
+
+// Sibling hashes from the leaf up; proves the slot's content, or its absence
+#[derive(Debug, Clone)]
+pub struct InclusionProof {
+    pub slot: u64,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl InclusionProof {
+    // Root of the tree with `state` in this slot and every other slot unchanged
+    pub fn root_with(&self, state: Option<&EncryptedState>) -> [u8; 32] {
+        let leaf = state.map_or([0u8; 32], |state| store_leaf(self.slot, state));
+        self.siblings.iter().enumerate().fold(leaf, |node, (level, sibling)| {
+            if (self.slot >> level) & 1 == 0 {
+                store_node(&node, sibling)
+            } else {
+                store_node(sibling, &node)
+            }
+        })
+    }
+    
+    pub fn verify(&self, root: &[u8; 32], state: Option<&EncryptedState>) -> bool {
+        self.siblings.len() == STORE_DEPTH && self.root_with(state) == *root
+    }
+    
+    pub fn size_bytes(&self) -> usize {
+        8 + 32 * self.siblings.len()
+    }
+}
+
+// One write: the siblings are untouched by it, so the same proof ties the
+// old content to the old root and the new content to the new root
+#[derive(Debug, Clone)]
+pub struct SlotWrite {
+    pub old_root: [u8; 32],
+    pub new_root: [u8; 32],
+    pub proof: InclusionProof,
+}
+
+impl SlotWrite {
+    pub fn verify(&self, old: Option<&EncryptedState>, new: &EncryptedState) -> bool {
+        self.proof.verify(&self.old_root, old) && self.proof.verify(&self.new_root, Some(new))
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct EncryptedStore {
+    slots: HashMap<u64, EncryptedState>,
+    // Non-empty nodes below the root by (level, index); level 0 is the leaves
+    nodes: HashMap<(usize, u64), [u8; 32]>,
+    root: Option<[u8; 32]>,
+    // Slots read or written since the last fork
+    touched: HashSet<u64>,
+    // Key epoch new slots are sealed under; advanced by applying a rotation
+    key_epoch: u64,
+}
+
+impl EncryptedStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    pub fn root(&self) -> [u8; 32] {
+        self.root.unwrap_or(empty_subtrees()[STORE_DEPTH])
+    }
+    
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+    
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+    
+    pub fn key_epoch(&self) -> u64 {
+        self.key_epoch
+    }
+    
+    // Owner's view of a slot; not counted as an access
+    pub fn get(&self, slot: u64) -> Option<&EncryptedState> {
+        self.slots.get(&slot)
+    }
+    
+    // Working copy for one execution: same contents, no slots touched yet
+    pub fn fork(&self) -> Self {
+        Self { touched: HashSet::new(), ..self.clone() }
+    }
+    
+    pub fn touched(&self) -> usize {
+        self.touched.len()
+    }
+    
+    pub fn prove(&self, slot: u64) -> InclusionProof {
+        let siblings = (0..STORE_DEPTH)
+            .map(|level| {
+                let sibling = (slot >> level) ^ 1;
+                self.nodes.get(&(level, sibling)).copied().unwrap_or(empty_subtrees()[level])
+            })
+            .collect();
+        InclusionProof { slot, siblings }
+    }
+    
+    pub fn read(&mut self, slot: u64) -> (Option<EncryptedState>, InclusionProof) {
+        self.touched.insert(slot);
+        (self.slots.get(&slot).cloned(), self.prove(slot))
+    }
+    
+    pub fn write(&mut self, slot: u64, state: EncryptedState) -> SlotWrite {
+        self.touched.insert(slot);
+        let proof = self.prove(slot);
+        let old_root = self.root();
+        let mut node = store_leaf(slot, &state);
+        for (level, sibling) in proof.siblings.iter().enumerate() {
+            self.nodes.insert((level, slot >> level), node);
+            node = if (slot >> level) & 1 == 0 { store_node(&node, sibling) } else { store_node(sibling, &node) };
+        }
+        self.root = Some(node);
+        self.slots.insert(slot, state);
+        SlotWrite { old_root, new_root: node, proof }
+    }
+    
+    // Swaps in a verified rotation: each re-encrypted state replaces the one
+    // it was made from, and the store moves to the new epoch. Refused for a
+    // rotation of states the store no longer holds, or to an older epoch.
+    pub fn apply_rotation(&mut self, rotation: &KeyRotation) -> bool {
+        let slots: Option<Vec<u64>> = rotation.states.iter().map(|state| state.key_path.slot_index()).collect();
+        let Some(slots) = slots else {
+            return false;
+        };
+        let current = slots
+            .iter()
+            .zip(&rotation.commitments)
+            .all(|(slot, (old, _))| self.slots.get(slot).is_some_and(|state| state.commitment == *old));
+        if !current || rotation.to_epoch <= self.key_epoch {
+            return false;
+        }
+        for (slot, state) in slots.into_iter().zip(&rotation.states) {
+            self.write(slot, state.clone());
+        }
+        self.key_epoch = rotation.to_epoch;
+        true
+    }
+    
+    pub fn head(&self) -> StoreHead {
+        let mut slots: Vec<(u64, [u8; 32])> = self.slots.iter().map(|(slot, state)| (*slot, state.commitment)).collect();
+        slots.sort_unstable();
+        StoreHead { root: self.root(), slots }
+    }
+    
+    // States first, then the head that references them
+    pub fn persist(&self, contract_id: &str, backend: &mut dyn StorageBackend) -> io::Result<()> {
+        for state in self.slots.values() {
+            backend.put_state(contract_id, state)?;
+        }
+        backend.put_head(contract_id, &self.head())
+    }
+    
+    // Rebuild from the stored head; the recomputed root must match it
+    pub fn load(contract_id: &str, backend: &dyn StorageBackend) -> io::Result<Option<Self>> {
+        let Some(head) = backend.get_head(contract_id)? else {
+            return Ok(None);
+        };
+        let mut store = Self::new();
+        for (slot, commitment) in &head.slots {
+            let state = backend
+                .get_state(contract_id, commitment)?
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "state referenced by head is missing"))?;
+            store.write(*slot, state);
+        }
+        if store.root() != head.root {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "rebuilt root does not match stored head"));
+        }
+        // Rotations move every state, so the newest epoch held is the current one
+        store.key_epoch = store.slots.values().filter_map(EncryptedState::key_epoch).max().unwrap_or(0);
+        store.touched.clear();
+        Ok(Some(store))
+    }
+}
+
+// Persistent storage. States are content-addressed by (contract id,
+// commitment); a contract's head (slot table and root) is replaced as a
+// whole, after the states it points to are durable.
+pub trait StorageBackend {
+    fn put_state(&mut self, contract_id: &str, state: &EncryptedState) -> io::Result<()>;
+    fn get_state(&self, contract_id: &str, commitment: &[u8; 32]) -> io::Result<Option<EncryptedState>>;
+    fn put_head(&mut self, contract_id: &str, head: &StoreHead) -> io::Result<()>;
+    fn get_head(&self, contract_id: &str) -> io::Result<Option<StoreHead>>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreHead {
+    pub root: [u8; 32],
+    pub slots: Vec<(u64, [u8; 32])>, // (slot, state commitment)
+}
+
+impl StoreHead {
+    pub(crate) fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.root.to_vec();
+        for (slot, commitment) in &self.slots {
+            bytes.extend_from_slice(&slot.to_le_bytes());
+            bytes.extend_from_slice(commitment);
+        }
+        bytes
+    }
+    
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (root, entries) = bytes.split_at_checked(32)?;
+        if entries.len() % 40 != 0 {
+            return None;
+        }
+        let slots = entries
+            .chunks_exact(40)
+            .map(|entry| {
+                let (slot, commitment) = entry.split_at(8);
+                Some((u64::from_le_bytes(slot.try_into().ok()?), commitment.try_into().ok()?))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { root: root.try_into().ok()?, slots })
+    }
+}
+
+#[derive(Default)]
+pub struct MemoryBackend {
+    states: HashMap<(String, [u8; 32]), EncryptedState>,
+    heads: HashMap<String, StoreHead>,
+}
+
+impl StorageBackend for MemoryBackend {
+    fn put_state(&mut self, contract_id: &str, state: &EncryptedState) -> io::Result<()> {
+        self.states.insert((contract_id.to_string(), state.commitment), state.clone());
+        Ok(())
+    }
+    
+    fn get_state(&self, contract_id: &str, commitment: &[u8; 32]) -> io::Result<Option<EncryptedState>> {
+        Ok(self.states.get(&(contract_id.to_string(), *commitment)).cloned())
+    }
+    
+    fn put_head(&mut self, contract_id: &str, head: &StoreHead) -> io::Result<()> {
+        self.heads.insert(contract_id.to_string(), head.clone());
+        Ok(())
+    }
+    
+    fn get_head(&self, contract_id: &str) -> io::Result<Option<StoreHead>> {
+        Ok(self.heads.get(contract_id).cloned())
+    }
+}
+
+// Flat files: <dir>/<hex contract id>/<hex commitment> per state, plus a
+// `head` file per contract
+pub struct FileBackend {
+    dir: PathBuf,
+}
+
+impl FileBackend {
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        Ok(Self { dir: dir.as_ref().to_path_buf() })
+    }
+    
+    fn contract_dir(&self, contract_id: &str) -> PathBuf {
+        self.dir.join(hex(contract_id.as_bytes()))
+    }
+    
+    // Write to a temp file, fsync, rename over the target, fsync the
+    // directory: a crash leaves the old file or the new one, never a torn
+    // one. A temp file left by a crash is simply overwritten next time.
+    fn write_atomic(path: &Path, bytes: &[u8]) -> io::Result<()> {
+        let tmp = path.with_extension("tmp");
+        let mut file = File::create(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)?;
+        if let Some(parent) = path.parent() {
+            File::open(parent)?.sync_all()?;
+        }
+        Ok(())
+    }
+    
+    fn read(path: &Path) -> io::Result<Option<Vec<u8>>> {
+        match fs::read(path) {
+            Ok(bytes) => Ok(Some(bytes)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl StorageBackend for FileBackend {
+    fn put_state(&mut self, contract_id: &str, state: &EncryptedState) -> io::Result<()> {
+        let dir = self.contract_dir(contract_id);
+        let path = dir.join(hex(&state.commitment));
+        if path.exists() {
+            return Ok(()); // content-addressed: already durable
+        }
+        fs::create_dir_all(&dir)?;
+        Self::write_atomic(&path, &state.to_bytes())
+    }
+    
+    fn get_state(&self, contract_id: &str, commitment: &[u8; 32]) -> io::Result<Option<EncryptedState>> {
+        let Some(bytes) = Self::read(&self.contract_dir(contract_id).join(hex(commitment)))? else {
+            return Ok(None);
+        };
+        match EncryptedState::from_bytes(&bytes) {
+            Some(state) if state.commitment == *commitment => Ok(Some(state)),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "stored state does not match its commitment")),
+        }
+    }
+    
+    fn put_head(&mut self, contract_id: &str, head: &StoreHead) -> io::Result<()> {
+        let dir = self.contract_dir(contract_id);
+        fs::create_dir_all(&dir)?;
+        Self::write_atomic(&dir.join("head"), &head.to_bytes())
+    }
+    
+    fn get_head(&self, contract_id: &str) -> io::Result<Option<StoreHead>> {
+        let Some(bytes) = Self::read(&self.contract_dir(contract_id).join("head"))? else {
+            return Ok(None);
+        };
+        StoreHead::from_bytes(&bytes)
+            .map(Some)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "malformed store head"))
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{COUNTER_CONTRACT_ID, COUNTER_SLOT, ContractHost, EncryptedCounter, ExecutionModel};
+    use crate::tests::KEY;
+    
+    fn kv_state(master: &ViewingKey, slot: u64, value: u64) -> EncryptedState {
+        EncryptedState::at(KeyPath::contract("kv").epoch(0).slot(slot), &value.to_le_bytes(), master).expect("sealed")
+    }
+    
+    // Multi-slot storage under one Merkle root: every write links the roots
+    #[test]
+    fn kv_write_proofs_link_old_and_new_roots() {
+        let master = ViewingKey::master(KEY);
+        let mut store = EncryptedStore::new();
+        for (index, slot) in [0u64, 1, 7, 1 << 40].into_iter().enumerate() {
+            let (old_root, old) = (store.root(), store.get(slot).cloned());
+            let state = kv_state(&master, slot, slot * 10);
+            let write = store.write(slot, state.clone());
+            assert!(write.verify(old.as_ref(), &state), "slot {}", slot);
+            assert_eq!(write.old_root, old_root);
+            if index == 0 {
+                assert_eq!(write.old_root, EncryptedStore::new().root(), "first write starts from the empty root");
+            }
+        }
+    }
+    
+    #[test]
+    fn kv_reads_prove_inclusion_and_absence() {
+        let master = ViewingKey::master(KEY);
+        let mut store = EncryptedStore::new();
+        for slot in [0u64, 1, 7] {
+            store.write(slot, kv_state(&master, slot, slot * 10));
+        }
+        let root = store.root();
+        let (state, proof) = store.read(7);
+        let state = state.expect("slot 7 written");
+        assert!(proof.verify(&root, Some(&state)), "inclusion proof verifies");
+        assert!(!proof.verify(&root, Some(&kv_state(&master, 0, 0))), "wrong state fails inclusion");
+        let (absent, absent_proof) = store.read(8);
+        assert!(absent.is_none() && absent_proof.verify(&root, None), "empty slot proven absent");
+        
+        let update = kv_state(&master, 7, 71);
+        let predicted = proof.root_with(Some(&update));
+        let write = store.write(7, update.clone());
+        assert_eq!(write.new_root, predicted, "root after write matches prediction");
+        assert_eq!(store.root(), predicted);
+        assert!(!proof.verify(&store.root(), Some(&state)), "old proof stale after the write");
+        assert_eq!(store.get(7).and_then(|state| state.open(&master)), update.open(&master));
+    }
+    
+    // A fresh directory under the system temp dir, removed on drop
+    struct ScratchDir(PathBuf);
+    
+    impl ScratchDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("ecliptica-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            Self(dir)
+        }
+    }
+    
+    impl Drop for ScratchDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+    
+    // Six TEE increments committed through a file-backed host; returns the
+    // persisted root
+    fn persist_counter(dir: &Path) -> [u8; 32] {
+        let mut counter = EncryptedCounter::new(0, KEY);
+        assert!(counter.prepare_enclave(KEY));
+        let backend = FileBackend::open(dir).expect("storage dir");
+        let host = ContractHost::new(counter).with_storage(Box::new(backend)).expect("host");
+        assert_eq!(host.run_load(ExecutionModel::TEEBased, 2, 3, KEY).committed, 6);
+        host.head().store.root()
+    }
+    
+    fn reopen(dir: &Path) -> io::Result<Option<EncryptedStore>> {
+        EncryptedStore::load(COUNTER_CONTRACT_ID, &FileBackend::open(dir)?)
+    }
+    
+    #[test]
+    fn memory_backend_round_trips_the_kv_store() {
+        let master = ViewingKey::master(KEY);
+        let mut store = EncryptedStore::new();
+        for slot in [0u64, 1, 7] {
+            store.write(slot, kv_state(&master, slot, slot * 10));
+        }
+        let mut memory = MemoryBackend::default();
+        store.persist("kv", &mut memory).expect("persisted");
+        let loaded = EncryptedStore::load("kv", &memory).expect("loaded").expect("head stored");
+        assert!(loaded.head() == store.head());
+    }
+    
+    // Committed heads on disk survive the host going away
+    #[test]
+    fn file_backend_survives_reopen() {
+        let dir = ScratchDir::new("reopen");
+        let root = persist_counter(&dir.0);
+        let reopened = reopen(&dir.0).expect("reopened").expect("head stored");
+        assert_eq!(reopened.root(), root);
+        let state = reopened.get(COUNTER_SLOT).expect("counter slot");
+        assert_eq!(state.open(&EncryptedCounter::viewing_key(KEY)), Some(6u64.to_le_bytes().to_vec()));
+    }
+    
+    // A crash mid-write leaves a temp file next to the intact head
+    #[test]
+    fn interrupted_head_write_leaves_the_old_head() {
+        let dir = ScratchDir::new("torn");
+        let root = persist_counter(&dir.0);
+        let contract_dir = dir.0.join(hex(COUNTER_CONTRACT_ID.as_bytes()));
+        fs::write(contract_dir.join("head.tmp"), b"torn").expect("temp file");
+        assert_eq!(reopen(&dir.0).expect("reopened").map(|store| store.root()), Some(root));
+    }
+    
+    #[test]
+    fn corrupted_state_file_detected_on_reopen() {
+        let dir = ScratchDir::new("corrupt");
+        persist_counter(&dir.0);
+        let state = reopen(&dir.0).expect("reopened").and_then(|store| store.get(COUNTER_SLOT).cloned()).expect("counter slot");
+        let path = dir.0.join(hex(COUNTER_CONTRACT_ID.as_bytes())).join(hex(&state.commitment));
+        let mut bytes = fs::read(&path).expect("state file");
+        *bytes.last_mut().expect("non-empty state file") ^= 1;
+        fs::write(&path, bytes).expect("state file");
+        assert!(reopen(&dir.0).is_err());
+    }
+    
+    // Plaintexts of the sizes the contracts write; the last one is the only
+    // one above the smallest fixed bucket
+    fn padding_samples() -> Vec<Vec<u8>> {
+        vec![
+            41u64.to_le_bytes().to_vec(),
+            [100u64, 50, 0, 0, 0].iter().flat_map(|b| b.to_le_bytes()).collect(),
+            [120u64, 340, 275, 90].iter().flat_map(|b| b.to_le_bytes()).collect(),
+            EncryptedCounter::new(41, KEY).snapshot().store.head().to_bytes(),
+            vec![0u8; 65536],
+        ]
+    }
+    
+    // Distinct sealed sizes among the small samples under `policy`
+    fn small_sizes(policy: &PaddingPolicy) -> usize {
+        let samples = padding_samples();
+        let sizes = samples[..samples.len() - 1].iter().map(|plaintext| EncryptedState::padded(plaintext, KEY, policy).ciphertext.len());
+        sizes.collect::<HashSet<_>>().len()
+    }
+    
+    #[test]
+    fn every_padding_policy_round_trips() {
+        for policy in [PaddingPolicy::None, PaddingPolicy::PowerOfTwo, PaddingPolicy::fixed()] {
+            for plaintext in padding_samples() {
+                assert_eq!(EncryptedState::padded(&plaintext, KEY, &policy).decrypt(KEY), Some(plaintext), "{:?}", policy);
+            }
+        }
+    }
+    
+    #[test]
+    fn fixed_buckets_hide_small_state_sizes() {
+        assert_eq!(small_sizes(&PaddingPolicy::fixed()), 1);
+        assert_eq!(small_sizes(&PaddingPolicy::None), padding_samples().len() - 1, "no padding: every small state has its own size");
+    }
+    
+    #[test]
+    fn bad_padding_rejected_on_decrypt() {
+        let path = KeyPath::master();
+        let bad_padding = EncryptedState {
+            ciphertext: aead_seal(&EncryptedState::aead_key(KEY), &[41, 0, 0, 0, 0, 0, 0, 0], &path.encode()),
+            commitment: [0; 32],
+            key_path: path,
+        };
+        assert!(bad_padding.decrypt(KEY).is_none());
+    }
+    
+    #[test]
+    fn padded_counter_stays_in_its_bucket() {
+        let mut counter = EncryptedCounter::new(41, KEY).with_padding(PaddingPolicy::fixed(), KEY);
+        for model in [ExecutionModel::ClientSideZK, ExecutionModel::TEEBased] {
+            counter.increment(model, KEY).expect("increment");
+            assert_eq!(counter.state().padded_len(), 256, "{:?}", model);
+        }
+        assert_eq!(counter.decrypt_value(counter.state(), KEY), Ok(43));
+    }
+    
+    // A truncated ciphertext has no bucket to stay in
+    #[test]
+    fn degenerate_policies_fall_back_to_framing() {
+        let truncated = EncryptedState { ciphertext: vec![0; AEAD_OVERHEAD], commitment: [0; 32], key_path: KeyPath::master() };
+        for policy in [PaddingPolicy::Buckets(vec![0]), PaddingPolicy::Buckets(Vec::new()), truncated.same_bucket()] {
+            assert_eq!(policy.padded_len(8), 9, "{:?}", policy);
+        }
+        assert_eq!(PaddingPolicy::Buckets(vec![0, 16]).padded_len(20), 32, "zero buckets are skipped, not divided by");
+    }
+}
@@ -0,0 +1,569 @@
+use ml_dsa::{KeyGen, KeyPair, MlDsa65, Signature, VerifyingKey};
+use ml_dsa::signature::{Signer, Verifier};
+use ml_kem::kem::{Decapsulate, Encapsulate};
+use ml_kem::{Ciphertext, EncodedSizeUser, KemCore, MlKem512};
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use winterfell::math::fields::f128::BaseElement;
+use crate::{CounterCalled, CounterSnapshot, EncryptedCounter, ExecutionError, decode_word};
+use crate::gas::GasMeter;
+use crate::keys::{EncryptedEvent, KeyPath, ViewingKey, aead_open, aead_seal, derive_key};
+use crate::storage::EncryptedState;
+use crate::vm::{Program, run_on_slot};
+use crate::zk::{CounterTransition, value_commitment};
+
+// An enclave's measurement: the hash of the code it loads
+pub fn measure(code: &[u8]) -> [u8; 32] {
+    derive_key(b"ecliptica/measurement", &[code])
+}
+
+// Software stand-in for SGX/SEV hardware: owns the ML-DSA attestation key
+// and the root secret enclave sealing keys are derived from
+pub struct SimulatedPlatform {
+    attestation_key: KeyPair<MlDsa65>,
+    sealing_secret: [u8; 32],
+}
+
+impl SimulatedPlatform {
+    pub fn new() -> Self {
+        let mut rng = rand::thread_rng();
+        let mut sealing_secret = [0u8; 32];
+        rng.fill(&mut sealing_secret);
+        Self {
+            attestation_key: MlDsa65::key_gen(&mut rng),
+            sealing_secret,
+        }
+    }
+    
+    // Published by the platform vendor; clients pin it
+    pub fn verifying_key(&self) -> VerifyingKey<MlDsa65> {
+        self.attestation_key.verifying_key().clone()
+    }
+}
+
+impl Default for SimulatedPlatform {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Platform-signed statement that an enclave with `measurement` holds the
+// ML-KEM key bound in `report_data`, fresh for the client's `nonce`
+#[derive(Debug, Clone)]
+pub struct AttestationReport {
+    pub measurement: [u8; 32],
+    pub nonce: [u8; 32],
+    pub report_data: [u8; 32],
+    pub kem_key: <MlKem512 as KemCore>::EncapsulationKey,
+    // Enclave's own key for signing the transitions it executes
+    pub signing_key: VerifyingKey<MlDsa65>,
+    pub signature: Signature<MlDsa65>,
+}
+
+impl AttestationReport {
+    fn signed_payload(measurement: &[u8; 32], nonce: &[u8; 32], report_data: &[u8; 32]) -> Vec<u8> {
+        [b"ecliptica/attestation-v1".as_slice(), measurement, nonce, report_data].concat()
+    }
+    
+    fn report_data(kem_key: &<MlKem512 as KemCore>::EncapsulationKey, signing_key: &VerifyingKey<MlDsa65>) -> [u8; 32] {
+        derive_key(b"ecliptica/report-data", &[&kem_key.as_bytes(), &signing_key.encode()])
+    }
+    
+    pub fn size_bytes(&self) -> usize {
+        3 * 32 + self.kem_key.as_bytes().len() + self.signing_key.encode().len() + self.signature.encode().len()
+    }
+}
+
+// Input encrypted to an ML-KEM key holder: encapsulation plus AES-GCM under
+// the derived key. Client inputs to an enclave are bound to its measurement.
+#[derive(Clone)]
+pub struct EncryptedInput {
+    kem_ciphertext: Ciphertext<MlKem512>,
+    sealed: Vec<u8>,
+}
+
+impl EncryptedInput {
+    pub fn seal(kem_key: &<MlKem512 as KemCore>::EncapsulationKey, plaintext: &[u8], aad: &[u8]) -> Self {
+        let (kem_ciphertext, shared) = kem_key
+            .encapsulate(&mut rand::thread_rng())
+            .expect("ML-KEM encapsulation");
+        let key = derive_key(b"ecliptica/kem-input", &[&shared[..]]);
+        Self {
+            kem_ciphertext,
+            sealed: aead_seal(&key, plaintext, aad),
+        }
+    }
+    
+    pub fn open(&self, kem_dk: &<MlKem512 as KemCore>::DecapsulationKey, aad: &[u8]) -> Option<Vec<u8>> {
+        let shared = kem_dk.decapsulate(&self.kem_ciphertext).ok()?;
+        let key = derive_key(b"ecliptica/kem-input", &[&shared[..]]);
+        aead_open(&key, &self.sealed, aad)
+    }
+    
+    fn commitment(&self) -> [u8; 32] {
+        derive_key(b"ecliptica/kem-input/commitment", &[&self.kem_ciphertext[..], &self.sealed])
+    }
+}
+
+// Simulated enclave: holds contract keys sealed to its measurement and
+// decrypts client inputs only inside its boundary
+pub struct Enclave {
+    platform: Arc<SimulatedPlatform>,
+    measurement: [u8; 32],
+    // The loaded code, if it is valid bytecode; the enclave runs nothing else
+    program: Option<Program>,
+    sealing_key: [u8; 32],
+    sealed_keys: HashMap<String, Vec<u8>>,
+    kem_dk: <MlKem512 as KemCore>::DecapsulationKey,
+    kem_ek: <MlKem512 as KemCore>::EncapsulationKey,
+    signing_key: Box<KeyPair<MlDsa65>>, // boxed: ML-DSA keys run to ~100KB inline
+}
+
+impl Enclave {
+    pub fn launch(platform: Arc<SimulatedPlatform>, code: &[u8]) -> Self {
+        let measurement = measure(code);
+        let sealing_key = derive_key(b"ecliptica/sealing", &[&platform.sealing_secret, &measurement]);
+        let mut rng = rand::thread_rng();
+        let (kem_dk, kem_ek) = MlKem512::generate(&mut rng);
+        Self {
+            platform,
+            measurement,
+            program: Program::decode(code),
+            sealing_key,
+            sealed_keys: HashMap::new(),
+            kem_dk,
+            kem_ek,
+            signing_key: Box::new(MlDsa65::key_gen(&mut rng)),
+        }
+    }
+    
+    pub fn measurement(&self) -> [u8; 32] {
+        self.measurement
+    }
+    
+    pub fn attest(&self, nonce: [u8; 32]) -> AttestationReport {
+        let signing_key = self.signing_key.verifying_key().clone();
+        let report_data = AttestationReport::report_data(&self.kem_ek, &signing_key);
+        let payload = AttestationReport::signed_payload(&self.measurement, &nonce, &report_data);
+        AttestationReport {
+            measurement: self.measurement,
+            nonce,
+            report_data,
+            kem_key: self.kem_ek.clone(),
+            signing_key,
+            signature: self.platform.attestation_key.signing_key().sign(&payload),
+        }
+    }
+    
+    // Contract key arrives as an encrypted client input and is only ever
+    // stored sealed under the measurement-bound sealing key
+    pub fn provision_key(&mut self, contract_id: &str, input: &EncryptedInput) -> bool {
+        let Some(key) = self.open_input(input) else {
+            return false;
+        };
+        let sealed = aead_seal(&self.sealing_key, &key, contract_id.as_bytes());
+        self.sealed_keys.insert(contract_id.to_string(), sealed);
+        true
+    }
+    
+    pub fn has_key(&self, contract_id: &str) -> bool {
+        self.sealed_keys.contains_key(contract_id)
+    }
+    
+    // Hybrid model: STARK that the counter in `new_state` is the one in
+    // `snapshot` plus one, anchored to the snapshot's value commitment.
+    // Like execution, the values never leave the enclave. `stall` delays
+    // the prover (fault injection).
+    pub fn prove_counter_step(
+        &self,
+        contract_id: &str,
+        snapshot: &CounterSnapshot,
+        new_state: &EncryptedState,
+        deadline: Duration,
+        stall: Option<Duration>,
+    ) -> Result<(CounterTransition, Vec<u8>), ExecutionError> {
+        let key = self.state_key(contract_id, new_state).ok_or(ExecutionError::AttestationFailed)?;
+        let open = |state: &EncryptedState| decode_word(&state.open(&key).ok_or(ExecutionError::DecryptFailed)?);
+        let (old_value, new_value) = (open(snapshot.state())?, open(new_state)?);
+        EncryptedCounter::generate_zk_proof(snapshot, new_state, &key, (old_value, new_value), deadline, stall)
+    }
+    
+    // Commitment to the counter in `state`, computed where the value is
+    // readable: inside the enclave
+    pub fn commit_value(&self, contract_id: &str, state: &EncryptedState) -> Result<BaseElement, ExecutionError> {
+        let key = self.state_key(contract_id, state).ok_or(ExecutionError::AttestationFailed)?;
+        let value = decode_word(&state.open(&key).ok_or(ExecutionError::DecryptFailed)?)?;
+        let blinding = state.value_blinding(&key).ok_or(ExecutionError::DecryptFailed)?;
+        Ok(value_commitment(value, blinding, &state.commitment))
+    }
+    
+    // Runs the loaded program on the decrypted slot with the call arguments
+    // from `input`, metered by `gas`; plaintext never leaves the enclave.
+    // The enclave signs only transitions it computed itself, over the
+    // user's sealed `call`, which must carry the same arguments.
+    pub fn execute_program(
+        &self,
+        contract_id: &str,
+        slot: u64,
+        state: &EncryptedState,
+        input: &EncryptedInput,
+        call: &EncryptedEvent,
+        gas: &mut GasMeter,
+    ) -> Result<(EncryptedState, Signature<MlDsa65>), ExecutionError> {
+        let program = self.program.as_ref().ok_or(ExecutionError::AttestationFailed)?;
+        let key = self.contract_key(contract_id).ok_or(ExecutionError::AttestationFailed)?;
+        let state_key = self.state_key(contract_id, state).ok_or(ExecutionError::AttestationFailed)?;
+        let args = self
+            .open_input(input)
+            .ok_or(ExecutionError::DecryptFailed)?
+            .chunks(8)
+            .map(decode_word)
+            .collect::<Result<Vec<_>, _>>()?;
+        if Self::call_args(&key, call)? != args {
+            return Err(ExecutionError::MalformedState);
+        }
+        let value = decode_word(&state.open(&state_key).ok_or(ExecutionError::DecryptFailed)?)?;
+        let value = gas.run(program, |program| run_on_slot(program, &(), slot, value, &args))?;
+        let new_state = state.reseal(&value.to_le_bytes(), &state_key, &state.same_bucket()).ok_or(ExecutionError::DecryptFailed)?;
+        let payload = transition_payload(contract_id, &state.commitment, &new_state.commitment, &call.commitment());
+        let signature = self.signing_key.signing_key().sign(&payload);
+        Ok((new_state, signature))
+    }
+    
+    fn call_args(key: &ViewingKey, call: &EncryptedEvent) -> Result<Vec<u64>, ExecutionError> {
+        Ok(call.decode::<CounterCalled>(key).ok_or(ExecutionError::DecryptFailed)?.args)
+    }
+    
+    // Fraud check: re-run the program from `previous` with the published call
+    // and compare with `claimed` inside the enclave; only the verdict leaves.
+    // A call other than the one the user authorised, or a claimed state that
+    // does not open, is a mismatch.
+    pub fn check_transition(
+        &self,
+        contract_id: &str,
+        slot: u64,
+        previous: &EncryptedState,
+        call: &EncryptedEvent,
+        authorised: &[u8; 32],
+        claimed: &EncryptedState,
+    ) -> Result<bool, ExecutionError> {
+        let program = self.program.as_ref().ok_or(ExecutionError::AttestationFailed)?;
+        let key = self.contract_key(contract_id).ok_or(ExecutionError::AttestationFailed)?;
+        if call.commitment() != *authorised {
+            return Ok(false);
+        }
+        let args = Self::call_args(&key, call)?;
+        let state_key = self.state_key(contract_id, previous).ok_or(ExecutionError::AttestationFailed)?;
+        let value = decode_word(&previous.open(&state_key).ok_or(ExecutionError::DecryptFailed)?)?;
+        let (expected, _) = run_on_slot(program, &(), slot, value, &args).map_err(ExecutionError::Program)?;
+        Ok(claimed.key_path == previous.key_path
+            && claimed.open(&state_key).is_some_and(|plaintext| decode_word(&plaintext) == Ok(expected)))
+    }
+    
+    // Simulated breach (key extraction, side channel): signs a transition
+    // the enclave did not compute, over a call of its own whose arguments
+    // make `value` look right. Optimistic mode's challenges must catch it.
+    pub fn sign_forged_transition(
+        &self,
+        contract_id: &str,
+        previous: &EncryptedState,
+        value: u64,
+    ) -> Option<(EncryptedState, EncryptedEvent, Signature<MlDsa65>)> {
+        let (key, state_key) = (self.contract_key(contract_id)?, self.state_key(contract_id, previous)?);
+        let old_value = decode_word(&previous.open(&state_key)?).ok()?;
+        let call = EncryptedEvent::emit(&key, &CounterCalled { args: vec![value.wrapping_sub(old_value)] })?;
+        let new_state = previous.reseal(&value.to_le_bytes(), &state_key, &previous.same_bucket())?;
+        let payload = transition_payload(contract_id, &previous.commitment, &new_state.commitment, &call.commitment());
+        Some((new_state, call, self.signing_key.signing_key().sign(&payload)))
+    }
+    
+    // Key rotation: re-encrypt every state of one epoch under `to_epoch` and
+    // sign that the plaintexts are unchanged. The new epoch key is fresh
+    // material, so holders of the old epoch key or of the contract key
+    // cannot derive it; it stays sealed here and goes out to `owner` only.
+    pub fn rotate_keys(
+        &mut self,
+        contract_id: &str,
+        states: &[EncryptedState],
+        to_epoch: u64,
+        owner: &<MlKem512 as KemCore>::EncapsulationKey,
+    ) -> Option<KeyRotation> {
+        let from_epoch = states.first()?.key_epoch()?;
+        let old_key = self.state_key(contract_id, states.first()?)?;
+        let new_key = ViewingKey::fresh(KeyPath::contract(contract_id).epoch(to_epoch));
+        let rotated = states
+            .iter()
+            .map(|state| {
+                if state.key_epoch() != Some(from_epoch) {
+                    return None;
+                }
+                let plaintext = state.open(&old_key)?;
+                EncryptedState::at_padded(state.key_path.with_epoch(to_epoch)?, &plaintext, &new_key, &state.same_bucket())
+            })
+            .collect::<Option<Vec<_>>>()?;
+        
+        let exported = new_key.export_scoped(new_key.path())?;
+        let epoch_id = KeyRotation::epoch_id(contract_id, to_epoch);
+        self.sealed_keys.insert(epoch_id.clone(), aead_seal(&self.sealing_key, &exported, epoch_id.as_bytes()));
+        let epoch_key = EncryptedInput::seal(owner, &exported, epoch_id.as_bytes());
+        
+        let commitments: Vec<([u8; 32], [u8; 32])> = states
+            .iter()
+            .zip(&rotated)
+            .map(|(old, new)| (old.commitment, new.commitment))
+            .collect();
+        let digest = KeyRotation::digest(contract_id, from_epoch, to_epoch, &commitments, &epoch_key);
+        Some(KeyRotation {
+            contract_id: contract_id.to_string(),
+            from_epoch,
+            to_epoch,
+            states: rotated,
+            commitments,
+            epoch_key,
+            report: self.attest(digest),
+            signature: self.signing_key.signing_key().sign(&digest),
+        })
+    }
+    
+    fn unseal_key(&self, contract_id: &str) -> Option<Vec<u8>> {
+        aead_open(&self.sealing_key, self.sealed_keys.get(contract_id)?, contract_id.as_bytes())
+    }
+    
+    // Contracts are provisioned with their viewing key, exported scoped to
+    // the contract, so the enclave opens that contract's states only
+    fn contract_key(&self, contract_id: &str) -> Option<ViewingKey> {
+        ViewingKey::import(&self.unseal_key(contract_id)?)
+    }
+    
+    // Key for an epoch this enclave rotated into, or was provisioned with
+    fn epoch_key(&self, contract_id: &str, epoch: u64) -> Option<ViewingKey> {
+        ViewingKey::import(&self.unseal_key(&KeyRotation::epoch_id(contract_id, epoch))?)
+    }
+    
+    // Key that opens `state`: its epoch's, if rotated, else the contract key
+    fn state_key(&self, contract_id: &str, state: &EncryptedState) -> Option<ViewingKey> {
+        let epoch_key = state.key_epoch().and_then(|epoch| self.epoch_key(contract_id, epoch));
+        epoch_key.or_else(|| self.contract_key(contract_id))
+    }
+    
+    fn open_input(&self, input: &EncryptedInput) -> Option<Vec<u8>> {
+        input.open(&self.kem_dk, &self.measurement)
+    }
+}
+
+// Enclave-signed statement that each new state holds the same plaintext as
+// the old one at the same position, now under the next key epoch
+#[derive(Clone)]
+pub struct KeyRotation {
+    pub contract_id: String,
+    pub from_epoch: u64,
+    pub to_epoch: u64,
+    pub states: Vec<EncryptedState>,
+    pub commitments: Vec<([u8; 32], [u8; 32])>, // (old, new) per state
+    pub epoch_key: EncryptedInput,                // new epoch's viewing key, to the owner
+    pub report: AttestationReport,                // nonce = statement digest
+    pub signature: Signature<MlDsa65>,
+}
+
+impl KeyRotation {
+    fn digest(
+        contract_id: &str,
+        from_epoch: u64,
+        to_epoch: u64,
+        commitments: &[([u8; 32], [u8; 32])],
+        epoch_key: &EncryptedInput,
+    ) -> [u8; 32] {
+        let pairs: Vec<u8> = commitments.iter().flat_map(|(old, new)| [*old, *new].concat()).collect();
+        derive_key(
+            b"ecliptica/key-rotation",
+            &[contract_id.as_bytes(), &from_epoch.to_le_bytes(), &to_epoch.to_le_bytes(), &pairs, &epoch_key.commitment()],
+        )
+    }
+    
+    // Name an epoch key is sealed under, and bound to when sent out
+    pub(crate) fn epoch_id(contract_id: &str, epoch: u64) -> String {
+        format!("{}/epoch/{}", contract_id, epoch)
+    }
+    
+    // Owner side: the new epoch's viewing key, scoped to that epoch
+    pub fn open_epoch_key(&self, owner: &<MlKem512 as KemCore>::DecapsulationKey) -> Option<ViewingKey> {
+        let exported = self.epoch_key.open(owner, Self::epoch_id(&self.contract_id, self.to_epoch).as_bytes())?;
+        ViewingKey::import(&exported).filter(|key| key.path() == &KeyPath::contract(&self.contract_id).epoch(self.to_epoch))
+    }
+    
+    // Anyone holding the old states checks the rotation without any key
+    pub fn verify(&self, old_states: &[EncryptedState], platform_key: &VerifyingKey<MlDsa65>, expected_measurement: &[u8; 32]) -> bool {
+        let digest = Self::digest(&self.contract_id, self.from_epoch, self.to_epoch, &self.commitments, &self.epoch_key);
+        let positions_match = old_states.len() == self.states.len()
+            && self.commitments.len() == self.states.len()
+            && old_states.iter().zip(&self.states).zip(&self.commitments).all(|((old, new), (old_c, new_c))| {
+                old.commitment == *old_c
+                    && new.commitment == *new_c
+                    && new.commitment_is_valid()
+                    && old.key_epoch() == Some(self.from_epoch)
+                    && new.key_epoch() == Some(self.to_epoch)
+                    && old.key_path.with_epoch(self.to_epoch).as_ref() == Some(&new.key_path)
+            });
+        
+        positions_match
+            && verify_attestation(&self.report, platform_key, expected_measurement, &digest)
+            && self.report.signing_key.verify(&digest, &self.signature).is_ok()
+    }
+}
+
+// Client side: accept the enclave only if the pinned platform key signed
+// this exact measurement, our nonce and the KEM key we are about to use
+pub fn verify_attestation(
+    report: &AttestationReport,
+    platform_key: &VerifyingKey<MlDsa65>,
+    expected_measurement: &[u8; 32],
+    nonce: &[u8; 32],
+) -> bool {
+    let report_data = AttestationReport::report_data(&report.kem_key, &report.signing_key);
+    let payload = AttestationReport::signed_payload(&report.measurement, &report.nonce, &report.report_data);
+    
+    report.measurement == *expected_measurement
+        && report.nonce == *nonce
+        && report.report_data == report_data
+        && platform_key.verify(&payload, &report.signature).is_ok()
+}
+
+// What enclaves and MPC parties sign for each state transition they execute.
+// `call` commits to the call the user sealed (its CounterCalled event), so
+// a signature cannot be moved to arguments the user never sent.
+pub(crate) fn transition_payload(contract_id: &str, previous: &[u8; 32], new: &[u8; 32], call: &[u8; 32]) -> Vec<u8> {
+    [b"ecliptica/transition-v2".as_slice(), contract_id.as_bytes(), previous, new, call].concat()
+}
+
+// Only call with a report that passed verify_attestation
+pub fn encrypt_input_for_enclave(report: &AttestationReport, input: &[u8]) -> EncryptedInput {
+    EncryptedInput::seal(&report.kem_key, input, &report.measurement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{COUNTER_CONTRACT_CODE, COUNTER_CONTRACT_ID, ExecutionModel};
+    use crate::storage::EncryptedStore;
+    use crate::tests::{KEY, sealed_tree, wasm_guest};
+    use crate::wasm::{WASM_DEFAULT_FUEL, WasmHost};
+    
+    // Clients refuse enclaves with the wrong code, platform or nonce
+    #[test]
+    fn attestation_pins_platform_code_and_nonce() {
+        let platform = Arc::new(SimulatedPlatform::new());
+        let expected = measure(COUNTER_CONTRACT_CODE);
+        let pinned = platform.verifying_key();
+        let nonce = [7u8; 32];
+        let report = Enclave::launch(platform.clone(), COUNTER_CONTRACT_CODE).attest(nonce);
+        let tampered = Enclave::launch(platform, b"counter v1 with a backdoor");
+        let rogue = Enclave::launch(Arc::new(SimulatedPlatform::new()), COUNTER_CONTRACT_CODE);
+        assert!(verify_attestation(&report, &pinned, &expected, &nonce), "genuine enclave accepted");
+        assert!(!verify_attestation(&report, &pinned, &expected, &[8u8; 32]), "replayed report rejected");
+        assert!(!verify_attestation(&tampered.attest(nonce), &pinned, &expected, &nonce), "tampered code rejected");
+        assert!(!verify_attestation(&rogue.attest(nonce), &pinned, &expected, &nonce), "unknown platform rejected");
+    }
+    
+    // The ledger's epoch-0 states, rotated to epoch 1 by an attested enclave
+    // for the owner holding `owner_ek`
+    fn rotated_ledger(
+        master: &ViewingKey,
+        owner_ek: &<MlKem512 as KemCore>::EncapsulationKey,
+    ) -> (Vec<EncryptedState>, KeyRotation, VerifyingKey<MlDsa65>) {
+        let ledger_states: Vec<EncryptedState> = sealed_tree(master)
+            .into_iter()
+            .filter(|(path, _, _)| KeyPath::contract("ledger").epoch(0).contains(path))
+            .map(|(_, _, state)| state)
+            .collect();
+        let platform = Arc::new(SimulatedPlatform::new());
+        let mut enclave = Enclave::launch(platform.clone(), COUNTER_CONTRACT_CODE);
+        let report = enclave.attest([7u8; 32]);
+        let contract_key = master.export_scoped(&KeyPath::contract("ledger")).expect("contract export");
+        assert!(enclave.provision_key("ledger", &encrypt_input_for_enclave(&report, &contract_key)));
+        let rotation = enclave.rotate_keys("ledger", &ledger_states, 1, owner_ek).expect("rotation");
+        (ledger_states, rotation, platform.verifying_key())
+    }
+    
+    // History stays readable with old keys; rotated states and new writes do not
+    #[test]
+    fn rotation_moves_states_to_the_new_epoch() {
+        let master = ViewingKey::master(KEY);
+        let (owner_dk, owner_ek) = MlKem512::generate(&mut rand::thread_rng());
+        let (ledger_states, rotation, pinned) = rotated_ledger(&master, &owner_ek);
+        let expected = measure(COUNTER_CONTRACT_CODE);
+        let new_key = rotation.open_epoch_key(&owner_dk).expect("owner opens the epoch key");
+        let old_epoch_key = master.derive(&KeyPath::contract("ledger").epoch(0)).expect("epoch key");
+        let contract_key = master.derive(&KeyPath::contract("ledger")).expect("contract key");
+        assert!(rotation.verify(&ledger_states, &pinned, &expected), "rotation attestation verifies");
+        for (old, new) in ledger_states.iter().zip(&rotation.states) {
+            assert!(old.open(&master).is_some());
+            assert_eq!(old.open(&master), new.open(&new_key), "plaintext unchanged under the new epoch key");
+            assert_eq!(new.key_epoch(), Some(1));
+            assert!(old.open(&old_epoch_key).is_some(), "old epoch key still opens history");
+            assert!(new.open(&old_epoch_key).is_none(), "old epoch key cannot open rotated states");
+            assert!(new.open(&contract_key).is_none(), "contract key cannot open rotated states");
+        }
+        assert!(rotation.open_epoch_key(&MlKem512::generate(&mut rand::thread_rng()).0).is_none(), "epoch key opens for the owner only");
+        
+        // Applied to the ledger's store, the rotation moves its epoch: a
+        // contract writing a new slot seals it there
+        let mut store = EncryptedStore::new();
+        for state in &ledger_states {
+            store.write(state.key_path.slot_index().expect("slot state"), state.clone());
+        }
+        assert!(store.apply_rotation(&rotation));
+        assert_eq!(store.key_epoch(), 1);
+        assert!(!store.apply_rotation(&rotation), "the store no longer holds the states it rotated");
+        let host = WasmHost::new().expect("Wasmtime engine");
+        let writer = host.load(wasm_guest("(call $write (i64.const 2) (i32.const 0) (i32.const 8))")).expect("module loads");
+        let run = host.call(&writer, &store, &new_key, 0, WASM_DEFAULT_FUEL).expect("call");
+        let new_write = run.store.get(2).expect("slot 2 written");
+        assert_eq!(new_write.key_epoch(), Some(1));
+        assert!(new_write.open(&new_key).is_some());
+        assert!(new_write.open(&old_epoch_key).is_none(), "old epoch key cannot open new writes");
+    }
+    
+    // A rotated counter keeps running, each new state sealed at the new
+    // epoch; FHE's ciphertext is keyless, and revealed back at the new epoch
+    #[test]
+    fn rotated_counter_seals_at_the_new_epoch() {
+        let mut counter = EncryptedCounter::new(41, KEY);
+        let (owner_dk, _) = MlKem512::generate(&mut rand::thread_rng());
+        let old_epoch_key = EncryptedCounter::viewing_key(KEY).derive(&KeyPath::contract(COUNTER_CONTRACT_ID).epoch(0)).expect("epoch key");
+        let rotation = counter.rotate_keys(KEY, &owner_dk).expect("rotation");
+        assert_eq!((rotation.from_epoch, rotation.to_epoch), (0, 1));
+        assert_eq!(counter.state().key_epoch(), Some(1));
+        for model in [ExecutionModel::ClientSideZK, ExecutionModel::TEEBased, ExecutionModel::HybridTEEZK] {
+            counter.increment(model, KEY).unwrap_or_else(|error| panic!("{:?}: {}", model, error));
+            assert_eq!(counter.state().key_epoch(), Some(1), "{:?}", model);
+        }
+        counter.increment(ExecutionModel::FHEBased, KEY).expect("FHE increment");
+        assert_eq!(counter.reveal_fhe(KEY), Some(45));
+        assert_eq!(counter.state().key_epoch(), Some(1));
+        assert!(counter.state().open(&old_epoch_key).is_none(), "old epoch key cannot open later states");
+        let verifier = counter.attested_enclave(KEY).expect("a fresh enclave is provisioned with the epoch key");
+        assert!(verifier.commit_value(COUNTER_CONTRACT_ID, counter.state()).is_ok());
+    }
+    
+    #[test]
+    fn rotation_rejects_substituted_reencryption() {
+        let master = ViewingKey::master(KEY);
+        let (owner_dk, owner_ek) = MlKem512::generate(&mut rand::thread_rng());
+        let (ledger_states, rotation, pinned) = rotated_ledger(&master, &owner_ek);
+        let new_key = rotation.open_epoch_key(&owner_dk).expect("owner opens the epoch key");
+        let mut forged = rotation.clone();
+        let substitute = EncryptedState::at(forged.states[0].key_path.clone(), b"ledger/0/0 + 1000", &new_key).expect("sealed");
+        forged.commitments[0].1 = substitute.commitment;
+        forged.states[0] = substitute;
+        assert!(!forged.verify(&ledger_states, &pinned, &measure(COUNTER_CONTRACT_CODE)));
+    }
+    
+    #[test]
+    fn no_attested_enclave_is_an_attestation_failure() {
+        let counter = EncryptedCounter::new(41, KEY);
+        let (result, _) = counter.execute(ExecutionModel::TEEBased, &counter.snapshot(), KEY);
+        assert_eq!(result.error, Some(ExecutionError::AttestationFailed));
+    }
+}
@@ -0,0 +1,489 @@
+use rand::Rng;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::span::Id;
+use tracing::Subscriber;
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::registry::LookupSpan;
+
+// Phase timing: each model wraps its steps in tracing spans named after the
+// phase; a layer on the driving thread turns them into exclusive durations
+// (entering a nested phase pauses the enclosing one), so phases never overlap
+const PHASE_TARGET: &str = "ecliptica::phase";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Phase {
+    Preprocess,  // MPC offline: triples, input masks
+    Attest,
+    Share,
+    Decrypt,
+    Compute,
+    Encrypt,
+    Network,     // attributed from NetworkStats, not a span
+    Reconstruct,
+    Prove,
+    Verify,
+    Setup,       // everything outside the phases above: forks, glue, result assembly
+}
+
+impl Phase {
+    pub const ALL: [Phase; 11] = [
+        Phase::Preprocess,
+        Phase::Attest,
+        Phase::Share,
+        Phase::Decrypt,
+        Phase::Compute,
+        Phase::Encrypt,
+        Phase::Network,
+        Phase::Reconstruct,
+        Phase::Prove,
+        Phase::Verify,
+        Phase::Setup,
+    ];
+    
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Preprocess => "preprocess",
+            Phase::Attest => "attest",
+            Phase::Share => "share",
+            Phase::Decrypt => "decrypt",
+            Phase::Compute => "compute",
+            Phase::Encrypt => "encrypt",
+            Phase::Network => "network",
+            Phase::Reconstruct => "reconstruct",
+            Phase::Prove => "prove",
+            Phase::Verify => "verify",
+            Phase::Setup => "setup",
+        }
+    }
+    
+    // Span names must be literals, hence one arm per phase
+    pub fn span(self) -> tracing::Span {
+        match self {
+            Phase::Preprocess => tracing::info_span!(target: PHASE_TARGET, "preprocess"),
+            Phase::Attest => tracing::info_span!(target: PHASE_TARGET, "attest"),
+            Phase::Share => tracing::info_span!(target: PHASE_TARGET, "share"),
+            Phase::Decrypt => tracing::info_span!(target: PHASE_TARGET, "decrypt"),
+            Phase::Compute => tracing::info_span!(target: PHASE_TARGET, "compute"),
+            Phase::Encrypt => tracing::info_span!(target: PHASE_TARGET, "encrypt"),
+            Phase::Network => tracing::info_span!(target: PHASE_TARGET, "network"),
+            Phase::Reconstruct => tracing::info_span!(target: PHASE_TARGET, "reconstruct"),
+            Phase::Prove => tracing::info_span!(target: PHASE_TARGET, "prove"),
+            Phase::Verify => tracing::info_span!(target: PHASE_TARGET, "verify"),
+            Phase::Setup => tracing::info_span!(target: PHASE_TARGET, "setup"),
+        }
+    }
+    
+    fn from_span(metadata: &tracing::Metadata<'_>) -> Option<Self> {
+        if metadata.target() != PHASE_TARGET {
+            return None;
+        }
+        Self::ALL.into_iter().find(|phase| phase.name() == metadata.name())
+    }
+}
+
+#[derive(Default)]
+struct PhaseClock {
+    open: Vec<(Phase, Instant)>, // entered phases, innermost last, with resume time
+    busy: HashMap<Phase, Duration>,
+}
+
+struct PhaseLayer {
+    clock: Arc<Mutex<PhaseClock>>,
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for PhaseLayer {
+    fn on_enter(&self, id: &Id, ctx: Context<'_, S>) {
+        let Some(phase) = ctx.metadata(id).and_then(Phase::from_span) else {
+            return;
+        };
+        let now = Instant::now();
+        let mut clock = self.clock.lock().expect("phase clock poisoned");
+        if let Some(&(outer, since)) = clock.open.last() {
+            *clock.busy.entry(outer).or_default() += now - since;
+        }
+        clock.open.push((phase, now));
+    }
+    
+    fn on_exit(&self, id: &Id, ctx: Context<'_, S>) {
+        if ctx.metadata(id).and_then(Phase::from_span).is_none() {
+            return;
+        }
+        let now = Instant::now();
+        let mut clock = self.clock.lock().expect("phase clock poisoned");
+        if let Some((phase, since)) = clock.open.pop() {
+            *clock.busy.entry(phase).or_default() += now - since;
+        }
+        if let Some(outer) = clock.open.last_mut() {
+            outer.1 = now;
+        }
+    }
+}
+
+// Exclusive time per phase for one execution, plus its wall-clock total
+#[derive(Debug, Clone, Default)]
+pub struct PhaseTimings {
+    busy: HashMap<Phase, Duration>,
+    pub total: Duration,
+}
+
+impl PhaseTimings {
+    // Run `f` with a phase-recording subscriber on this thread, inside a
+    // setup span that takes whatever no other phase covers. Spans on other
+    // threads (MPC parties) are not seen.
+    pub fn record<T>(f: impl FnOnce() -> T) -> (T, Self) {
+        let clock = Arc::new(Mutex::new(PhaseClock::default()));
+        let subscriber = tracing_subscriber::registry().with(PhaseLayer { clock: clock.clone() });
+        let (result, total) = tracing::subscriber::with_default(subscriber, || {
+            // First use of a span callsite registers it, and the first span
+            // entered sets up the registry's span stack; keep both off the clock
+            for phase in Phase::ALL {
+                phase.span().in_scope(|| ());
+            }
+            clock.lock().expect("phase clock poisoned").busy.clear();
+            let setup = Phase::Setup.span();
+            let start = Instant::now();
+            let entered = setup.enter();
+            let result = f();
+            drop(entered);
+            (result, start.elapsed())
+        });
+        let busy = std::mem::take(&mut clock.lock().expect("phase clock poisoned").busy);
+        (result, Self { busy, total })
+    }
+    
+    pub fn get(&self, phase: Phase) -> Duration {
+        self.busy.get(&phase).copied().unwrap_or_default()
+    }
+    
+    // Move time measured elsewhere (e.g. network wait on the party threads)
+    // out of the span that covered it
+    pub fn reattribute(&mut self, from: Phase, to: Phase, amount: Duration) {
+        let moved = amount.min(self.get(from));
+        *self.busy.entry(from).or_default() -= moved;
+        *self.busy.entry(to).or_default() += moved;
+    }
+    
+    pub fn breakdown(&self) -> ExecutionBreakdown {
+        let us = |phase| self.get(phase).as_micros() as u64;
+        ExecutionBreakdown {
+            preprocess_us: us(Phase::Preprocess),
+            attest_us: us(Phase::Attest),
+            share_us: us(Phase::Share),
+            decrypt_us: us(Phase::Decrypt),
+            compute_us: us(Phase::Compute),
+            encrypt_us: us(Phase::Encrypt),
+            network_us: us(Phase::Network),
+            reconstruct_us: us(Phase::Reconstruct),
+            prove_us: us(Phase::Prove),
+            verify_us: us(Phase::Verify),
+            setup_us: us(Phase::Setup),
+        }
+    }
+}
+
+// Plaintext cost of a contract: the same logic run unencrypted, timed in
+// batches since one call is below timer resolution. Median over samples.
+pub const BASELINE_SAMPLES: usize = 101;
+const BASELINE_BATCH: u32 = 1_000;
+
+// Target overhead of an encrypted model vs its contract's plaintext baseline
+pub const OVERHEAD_TARGET: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PlaintextBaseline {
+    pub contract: &'static str,
+    pub median_ns: f64,
+    pub samples: usize,
+}
+
+impl PlaintextBaseline {
+    pub fn measure<T>(contract: &'static str, mut run: impl FnMut() -> T) -> Self {
+        let mut samples: Vec<f64> = (0..BASELINE_SAMPLES)
+            .map(|_| {
+                let start = Instant::now();
+                for _ in 0..BASELINE_BATCH {
+                    std::hint::black_box(run());
+                }
+                start.elapsed().as_nanos() as f64 / BASELINE_BATCH as f64
+            })
+            .collect();
+        samples.sort_by(f64::total_cmp);
+        Self {
+            contract,
+            median_ns: samples[samples.len() / 2],
+            samples: samples.len(),
+        }
+    }
+    
+    pub fn print(&self) {
+        println!("Baseline ({} plaintext): {:.1}ns (median of {} × {} runs)", self.contract, self.median_ns, self.samples, BASELINE_BATCH);
+    }
+}
+
+// Timing-leakage test in the style of dudect (Reparaz, Balasch, Verbauwhede
+// 2017): time one path on secret inputs from two classes, interleaved at
+// random, and compare the two distributions with Welch's t-test. The test
+// is repeated on measurements cropped at upper percentiles, which strips
+// one-sided noise (interrupts, migrations). |t| above the threshold on any
+// crop means the timing depends on the secret.
+pub const DUDECT_T_THRESHOLD: f64 = 4.5;
+const DUDECT_CROP_PERCENTILES: [f64; 4] = [0.5, 0.75, 0.9, 0.99];
+
+// Running mean and variance (Welford)
+#[derive(Debug, Clone, Copy, Default)]
+struct TimingClass {
+    n: f64,
+    mean: f64,
+    m2: f64,
+}
+
+impl TimingClass {
+    fn push(&mut self, x: f64) {
+        self.n += 1.0;
+        let delta = x - self.mean;
+        self.mean += delta / self.n;
+        self.m2 += delta * (x - self.mean);
+    }
+    
+    fn variance(&self) -> f64 {
+        self.m2 / (self.n - 1.0).max(1.0)
+    }
+    
+    fn welch_t(&self, other: &TimingClass) -> f64 {
+        let se = (self.variance() / self.n.max(1.0) + other.variance() / other.n.max(1.0)).sqrt();
+        if se == 0.0 { 0.0 } else { (self.mean - other.mean) / se }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LeakageReport {
+    pub path: String,
+    pub samples: usize,
+    pub max_t: f64, // largest |t| over the uncropped and cropped tests
+}
+
+impl LeakageReport {
+    pub fn leaks(&self) -> bool {
+        self.max_t > DUDECT_T_THRESHOLD
+    }
+    
+    pub fn print(&self) {
+        println!(
+            "{}: |t| = {:.2} over {} samples{}",
+            self.path,
+            self.max_t,
+            self.samples,
+            if self.leaks() { " (timing depends on the secret)" } else { "" }
+        );
+    }
+}
+
+pub struct TimingHarness {
+    samples: usize,
+    // Calls per measurement, to get fast paths above timer resolution
+    batch: usize,
+}
+
+impl TimingHarness {
+    pub fn new(samples: usize) -> Self {
+        Self { samples, batch: 1 }
+    }
+    
+    pub fn with_batch(mut self, batch: usize) -> Self {
+        self.batch = batch.max(1);
+        self
+    }
+    
+    // `input(class)` builds one secret input of class 0 or 1, off the clock;
+    // `run` is the path under test
+    pub fn test<I, T>(&self, path: &str, mut input: impl FnMut(usize) -> I, mut run: impl FnMut(&I) -> T) -> LeakageReport {
+        let mut rng = rand::thread_rng();
+        let classes: Vec<usize> = (0..self.samples).map(|_| rng.gen_range(0..2)).collect();
+        let inputs: Vec<I> = classes.iter().map(|&class| input(class)).collect();
+        let timings: Vec<f64> = inputs
+            .iter()
+            .map(|input| {
+                let start = Instant::now();
+                for _ in 0..self.batch {
+                    std::hint::black_box(run(std::hint::black_box(input)));
+                }
+                start.elapsed().as_nanos() as f64
+            })
+            .collect();
+        
+        let mut sorted = timings.clone();
+        sorted.sort_by(f64::total_cmp);
+        let cutoffs = std::iter::once(f64::INFINITY)
+            .chain(DUDECT_CROP_PERCENTILES.iter().map(|p| sorted[((sorted.len() - 1) as f64 * p) as usize]));
+        let max_t = cutoffs
+            .map(|cutoff| {
+                let mut split = [TimingClass::default(); 2];
+                for (&time, &class) in timings.iter().zip(&classes) {
+                    if time <= cutoff {
+                        split[class].push(time);
+                    }
+                }
+                split[0].welch_t(&split[1]).abs()
+            })
+            .fold(0.0, f64::max);
+        LeakageReport { path: path.to_string(), samples: self.samples, max_t }
+    }
+}
+
+// Built from PhaseTimings; see Phase for what each field covers
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExecutionBreakdown {
+    pub preprocess_us: u64,
+    pub attest_us: u64,
+    pub share_us: u64,
+    pub decrypt_us: u64,
+    pub compute_us: u64,
+    pub encrypt_us: u64,
+    pub network_us: u64,
+    pub reconstruct_us: u64,
+    pub prove_us: u64,
+    pub verify_us: u64,
+    pub setup_us: u64,
+}
+
+impl ExecutionBreakdown {
+    pub fn phases(&self) -> [(Phase, u64); 11] {
+        [
+            (Phase::Preprocess, self.preprocess_us),
+            (Phase::Attest, self.attest_us),
+            (Phase::Share, self.share_us),
+            (Phase::Decrypt, self.decrypt_us),
+            (Phase::Compute, self.compute_us),
+            (Phase::Encrypt, self.encrypt_us),
+            (Phase::Network, self.network_us),
+            (Phase::Reconstruct, self.reconstruct_us),
+            (Phase::Prove, self.prove_us),
+            (Phase::Verify, self.verify_us),
+            (Phase::Setup, self.setup_us),
+        ]
+    }
+    
+    pub fn sum_us(&self) -> u64 {
+        self.phases().iter().map(|(_, us)| us).sum()
+    }
+    
+    // MPC online phase: everything input-dependent
+    pub fn online_us(&self) -> u64 {
+        self.sum_us() - self.preprocess_us - self.setup_us
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ml_kem::kem::{Decapsulate, Encapsulate};
+    use ml_kem::{KemCore, MlKem512};
+    use crate::{EncryptedCounter, ExecutionModel};
+    use crate::mpc::{SealedBidAuction, SharedValue};
+    use crate::storage::{EncryptedState, PaddingPolicy};
+    use crate::tests::{KEY, scaled_add};
+    
+    // Phase spans are exclusive and cover each run up to the tolerance
+    #[test]
+    fn phase_breakdowns_sum_to_total() {
+        for model in ExecutionModel::ALL {
+            let result = EncryptedCounter::new(0, KEY).increment(model, KEY).expect("increment");
+            assert!(result.phases_sum_to_total(), "{:?}: {}μs unattributed", model, result.unattributed_us());
+        }
+        let mut auction = SealedBidAuction::new(3, 5);
+        for bid in [120, 340, 275, 90] {
+            auction.submit_bid(bid).expect("bid in range");
+        }
+        let (_, result) = auction.run_mpc();
+        assert!(result.phases_sum_to_total(), "auction: {}μs unattributed", result.unattributed_us());
+    }
+    
+    #[test]
+    fn plaintext_baseline_runs_the_program() {
+        assert_eq!(EncryptedCounter::new(0, KEY).with_program(scaled_add(), &[3]).plaintext_call(41), Ok(47));
+        assert_eq!(EncryptedCounter::new(0, KEY).plaintext_call(u64::MAX), Ok(0), "wraps at u64::MAX like the VM");
+    }
+    
+    // Timing tests hold this so they do not run against each other
+    static TIMING: Mutex<()> = Mutex::new(());
+    
+    fn secret_value(class: usize) -> u64 {
+        if class == 0 { 0 } else { rand::thread_rng().gen() }
+    }
+    
+    // Secret-dependent timing on the paths that touch plaintext or key material
+    #[test]
+    fn no_timing_leak_on_secret_paths() {
+        let _timing = TIMING.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let harness = TimingHarness::new(20_000).with_batch(4);
+        let counter = EncryptedCounter::new(0, KEY);
+        let (kem_dk, kem_ek) = MlKem512::generate(&mut rand::thread_rng());
+        let reports = [
+            harness.test(
+                "AEAD seal, value 0 vs random",
+                |class| secret_value(class).to_le_bytes(),
+                |plaintext| EncryptedState::new(plaintext, KEY),
+            ),
+            harness.test(
+                "AEAD open, value 0 vs random",
+                |class| EncryptedState::new(&secret_value(class).to_le_bytes(), KEY),
+                |state| state.decrypt(KEY),
+            ),
+            harness.test(
+                "Counter decrypt, value 0 vs random",
+                |class| counter.seal(KEY, &secret_value(class).to_le_bytes(), &PaddingPolicy::None),
+                |state| counter.decrypt_value(state, KEY),
+            ),
+            harness.test(
+                "ML-KEM decapsulation, valid vs corrupted ciphertext",
+                |class| {
+                    let (mut ciphertext, _) = kem_ek.encapsulate(&mut rand::thread_rng()).expect("ML-KEM encapsulation");
+                    if class == 1 {
+                        let last = ciphertext.len() - 1;
+                        ciphertext[last] ^= 1;
+                    }
+                    ciphertext
+                },
+                |ciphertext| kem_dk.decapsulate(ciphertext).ok(),
+            ),
+            harness.test(
+                "Shamir reconstruction (3-of-5), value 0 vs random",
+                |class| SharedValue::share(secret_value(class), 3, 5),
+                |sharing| sharing.reconstruct(),
+            ),
+        ];
+        for report in reports {
+            assert!(!report.leaks(), "{}: t = {:.1}", report.path, report.max_t);
+        }
+    }
+    
+    // Control: an early-exit comparison must be caught
+    #[test]
+    fn harness_flags_an_early_exit_compare() {
+        let _timing = TIMING.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let control = TimingHarness::new(20_000).with_batch(4).test(
+            "Control: early-exit compare, mismatch at byte 0 vs none",
+            |class| {
+                let mut guess = vec![0u8; 4096];
+                guess[0] = (class == 0) as u8;
+                guess
+            },
+            |guess| guess.as_slice() == [0u8; 4096].as_slice(),
+        );
+        assert!(control.leaks(), "t = {:.1}", control.max_t);
+    }
+    
+    #[test]
+    fn same_bucket_no_timing_leak_on_open() {
+        let _timing = TIMING.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let bucket = PaddingPolicy::fixed();
+        let report = TimingHarness::new(20_000).with_batch(4).test(
+            "AEAD open, 8 vs 200-byte plaintext in one 256B bucket",
+            |class| EncryptedState::padded(&vec![1u8; if class == 0 { 8 } else { 200 }], KEY, &bucket),
+            |state| state.decrypt(KEY),
+        );
+        assert!(!report.leaks(), "t = {:.1}", report.max_t);
+    }
+}
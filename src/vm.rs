@@ -0,0 +1,389 @@
+use std::collections::HashMap;
+use crate::tee::measure;
+
+// Contract bytecode: a deterministic stack machine with a fixed instruction
+// set. No host calls and no clock, so every model computes the same result;
+// a step bound stops runaway loops. Arithmetic is in the executor's domain
+// (u64, F_p shares, LWE plaintexts mod 2^32), so contracts must keep values
+// below 2^32 for all models to agree.
+pub const VM_MAX_STEPS: usize = 10_000;
+pub const VM_MAX_STACK: usize = 256;
+
+// Encoding: one opcode byte, then a little-endian immediate for Push (u64),
+// Load/Store (slot, u64), Arg (u8) and Jnz (instruction index, u32)
+pub(crate) const OP_HALT: u8 = 0x00;
+const OP_PUSH: u8 = 0x01;
+pub(crate) const OP_LOAD: u8 = 0x02;
+pub(crate) const OP_STORE: u8 = 0x03;
+pub(crate) const OP_ARG: u8 = 0x04;
+const OP_POP: u8 = 0x05;
+const OP_DUP: u8 = 0x06;
+const OP_SWAP: u8 = 0x07;
+const OP_JNZ: u8 = 0x08;
+pub(crate) const OP_ADD: u8 = 0x10;
+const OP_SUB: u8 = 0x11;
+const OP_MUL: u8 = 0x12;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Instruction {
+    Halt,
+    Push(u64),   // public constant
+    Load(u64),   // slot value
+    Store(u64),  // pop into slot
+    Arg(u8),     // call argument
+    Pop,
+    Dup,
+    Swap,
+    Jnz(u32),    // pop, jump if non-zero
+    Add,
+    Sub,
+    Mul,
+}
+
+// Stack entry: public constants stay in the clear until combined with a secret
+#[derive(Debug, Clone)]
+pub enum Operand<W> {
+    Public(u64),
+    Secret(W),
+}
+
+impl<W: VmWord> Operand<W> {
+    fn into_word(self, context: &W::Context) -> W {
+        match self {
+            Operand::Public(value) => W::constant(context, value),
+            Operand::Secret(word) => word,
+        }
+    }
+}
+
+// A value the VM computes on: plaintext, a party's share, a ciphertext.
+// Steps a representation cannot take locally return None.
+pub trait VmWord: Clone {
+    // What it takes to turn a public constant into a word (MAC keys, ...)
+    type Context;
+    fn constant(context: &Self::Context, value: u64) -> Self;
+    fn add(&self, other: &Self) -> Self;
+    fn sub(&self, other: &Self) -> Self;
+    fn mul_public(&self, value: u64) -> Self;
+    // Secret × secret: needs interaction for shares, FHE depth for LWE
+    fn mul(&self, other: &Self) -> Option<Self>;
+    // Branching on a secret is only possible where it is not secret
+    fn is_zero(&self) -> Option<bool>;
+}
+
+impl VmWord for u64 {
+    type Context = ();
+    
+    fn constant(_: &(), value: u64) -> Self {
+        value
+    }
+    
+    fn add(&self, other: &Self) -> Self {
+        self.wrapping_add(*other)
+    }
+    
+    fn sub(&self, other: &Self) -> Self {
+        self.wrapping_sub(*other)
+    }
+    
+    fn mul_public(&self, value: u64) -> Self {
+        self.wrapping_mul(value)
+    }
+    
+    fn mul(&self, other: &Self) -> Option<Self> {
+        Some(self.wrapping_mul(*other))
+    }
+    
+    fn is_zero(&self) -> Option<bool> {
+        Some(*self == 0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum VmError {
+    StepLimit(usize),
+    StackUnderflow { pc: usize },
+    StackOverflow { pc: usize },
+    EmptySlot(u64),
+    MissingArg(u8),
+    Unsupported { pc: usize, instruction: Instruction },
+    BadJump { pc: usize, target: u32 },
+}
+
+impl std::fmt::Display for VmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VmError::StepLimit(steps) => write!(f, "step limit of {} reached", steps),
+            VmError::StackUnderflow { pc } => write!(f, "stack underflow at {}", pc),
+            VmError::StackOverflow { pc } => write!(f, "stack overflow at {}", pc),
+            VmError::EmptySlot(slot) => write!(f, "load from empty slot {}", slot),
+            VmError::MissingArg(index) => write!(f, "call has no argument {}", index),
+            VmError::Unsupported { pc, instruction } => {
+                write!(f, "{:?} at {} is not supported by this execution model", instruction, pc)
+            }
+            VmError::BadJump { pc, target } => write!(f, "jump from {} to {} is out of range", pc, target),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VmRun {
+    pub steps: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program {
+    code: Vec<Instruction>,
+    pub(crate) max_steps: usize,
+}
+
+impl Program {
+    pub fn new(code: Vec<Instruction>) -> Self {
+        Self { code, max_steps: VM_MAX_STEPS }
+    }
+    
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+    
+    pub fn decode(mut bytes: &[u8]) -> Option<Self> {
+        fn immediate<const N: usize>(bytes: &mut &[u8]) -> Option<[u8; N]> {
+            let (value, rest) = bytes.split_at_checked(N)?;
+            *bytes = rest;
+            value.try_into().ok()
+        }
+        let mut code = Vec::new();
+        while let Some((&opcode, rest)) = bytes.split_first() {
+            bytes = rest;
+            code.push(match opcode {
+                OP_HALT => Instruction::Halt,
+                OP_PUSH => Instruction::Push(u64::from_le_bytes(immediate(&mut bytes)?)),
+                OP_LOAD => Instruction::Load(u64::from_le_bytes(immediate(&mut bytes)?)),
+                OP_STORE => Instruction::Store(u64::from_le_bytes(immediate(&mut bytes)?)),
+                OP_ARG => Instruction::Arg(immediate::<1>(&mut bytes)?[0]),
+                OP_POP => Instruction::Pop,
+                OP_DUP => Instruction::Dup,
+                OP_SWAP => Instruction::Swap,
+                OP_JNZ => Instruction::Jnz(u32::from_le_bytes(immediate(&mut bytes)?)),
+                OP_ADD => Instruction::Add,
+                OP_SUB => Instruction::Sub,
+                OP_MUL => Instruction::Mul,
+                _ => return None,
+            });
+        }
+        Some(Self::new(code))
+    }
+    
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for instruction in &self.code {
+            match *instruction {
+                Instruction::Halt => bytes.push(OP_HALT),
+                Instruction::Push(value) => bytes.extend([&[OP_PUSH][..], &value.to_le_bytes()].concat()),
+                Instruction::Load(slot) => bytes.extend([&[OP_LOAD][..], &slot.to_le_bytes()].concat()),
+                Instruction::Store(slot) => bytes.extend([&[OP_STORE][..], &slot.to_le_bytes()].concat()),
+                Instruction::Arg(index) => bytes.extend([OP_ARG, index]),
+                Instruction::Pop => bytes.push(OP_POP),
+                Instruction::Dup => bytes.push(OP_DUP),
+                Instruction::Swap => bytes.push(OP_SWAP),
+                Instruction::Jnz(target) => bytes.extend([&[OP_JNZ][..], &target.to_le_bytes()].concat()),
+                Instruction::Add => bytes.push(OP_ADD),
+                Instruction::Sub => bytes.push(OP_SUB),
+                Instruction::Mul => bytes.push(OP_MUL),
+            }
+        }
+        bytes
+    }
+    
+    // What an enclave running this program attests to
+    pub fn measurement(&self) -> [u8; 32] {
+        measure(&self.encode())
+    }
+    
+    pub fn instructions(&self) -> &[Instruction] {
+        &self.code
+    }
+    
+    // Runs until Halt or the end of the code, updating `slots` in place.
+    // On error the slots may be partially written; callers discard them.
+    pub fn run<W: VmWord>(
+        &self,
+        context: &W::Context,
+        slots: &mut HashMap<u64, W>,
+        args: &[Operand<W>],
+    ) -> Result<VmRun, VmError> {
+        let mut stack: Vec<Operand<W>> = Vec::new();
+        let mut pc = 0;
+        let mut steps = 0;
+        while let Some(&instruction) = self.code.get(pc) {
+            if steps == self.max_steps {
+                return Err(VmError::StepLimit(self.max_steps));
+            }
+            steps += 1;
+            let at = pc;
+            pc += 1;
+            let mut pop = || stack.pop().ok_or(VmError::StackUnderflow { pc: at });
+            match instruction {
+                Instruction::Halt => break,
+                Instruction::Push(value) => stack.push(Operand::Public(value)),
+                Instruction::Load(slot) => {
+                    let word = slots.get(&slot).cloned().ok_or(VmError::EmptySlot(slot))?;
+                    stack.push(Operand::Secret(word));
+                }
+                Instruction::Store(slot) => {
+                    let word = pop()?.into_word(context);
+                    slots.insert(slot, word);
+                }
+                Instruction::Arg(index) => {
+                    let arg = args.get(index as usize).cloned().ok_or(VmError::MissingArg(index))?;
+                    stack.push(arg);
+                }
+                Instruction::Pop => {
+                    pop()?;
+                }
+                Instruction::Dup => {
+                    let top = stack.last().cloned().ok_or(VmError::StackUnderflow { pc: at })?;
+                    stack.push(top);
+                }
+                Instruction::Swap => {
+                    let len = stack.len();
+                    if len < 2 {
+                        return Err(VmError::StackUnderflow { pc: at });
+                    }
+                    stack.swap(len - 1, len - 2);
+                }
+                Instruction::Jnz(target) => {
+                    let taken = match pop()? {
+                        Operand::Public(value) => value != 0,
+                        Operand::Secret(word) => !word.is_zero().ok_or(VmError::Unsupported { pc: at, instruction })?,
+                    };
+                    if taken {
+                        if target as usize >= self.code.len() {
+                            return Err(VmError::BadJump { pc: at, target });
+                        }
+                        pc = target as usize;
+                    }
+                }
+                Instruction::Add | Instruction::Sub | Instruction::Mul => {
+                    let rhs = pop()?;
+                    let lhs = pop()?;
+                    let result = Self::arithmetic(context, instruction, lhs, rhs)
+                        .ok_or(VmError::Unsupported { pc: at, instruction })?;
+                    stack.push(result);
+                }
+            }
+            if stack.len() > VM_MAX_STACK {
+                return Err(VmError::StackOverflow { pc: at });
+            }
+        }
+        Ok(VmRun { steps })
+    }
+    
+    fn arithmetic<W: VmWord>(context: &W::Context, instruction: Instruction, lhs: Operand<W>, rhs: Operand<W>) -> Option<Operand<W>> {
+        use Operand::{Public, Secret};
+        Some(match (instruction, lhs, rhs) {
+            (Instruction::Add, Public(a), Public(b)) => Public(a.wrapping_add(b)),
+            (Instruction::Sub, Public(a), Public(b)) => Public(a.wrapping_sub(b)),
+            (Instruction::Mul, Public(a), Public(b)) => Public(a.wrapping_mul(b)),
+            (Instruction::Mul, Secret(a), Public(c)) | (Instruction::Mul, Public(c), Secret(a)) => Secret(a.mul_public(c)),
+            (Instruction::Mul, Secret(a), Secret(b)) => Secret(a.mul(&b)?),
+            (Instruction::Add, a, b) => Secret(a.into_word(context).add(&b.into_word(context))),
+            (Instruction::Sub, a, b) => Secret(a.into_word(context).sub(&b.into_word(context))),
+            _ => return None,
+        })
+    }
+}
+
+// The counter harness gives programs one slot: run on it with public args
+pub(crate) fn run_on_slot<W: VmWord>(
+    program: &Program,
+    context: &W::Context,
+    slot: u64,
+    word: W,
+    args: &[u64],
+) -> Result<(W, VmRun), VmError> {
+    let args: Vec<Operand<W>> = args.iter().map(|arg| Operand::Public(*arg)).collect();
+    let mut slots = HashMap::from([(slot, word)]);
+    let run = program.run(context, &mut slots, &args)?;
+    Ok((slots.remove(&slot).ok_or(VmError::EmptySlot(slot))?, run))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{COUNTER_SLOT, ContractHost, EncryptedCounter, ExecutionError, ExecutionModel};
+    use crate::fhe::LweSecretKey;
+    use crate::mpc::{AuthShare, Fp, ShareContext};
+    use crate::tests::{KEY, scaled_add};
+    
+    // Same bytecode under every model, no harness changes; the STARK covers
+    // the counter program only, so a hybrid call stands unproven
+    #[test]
+    fn bytecode_runs_under_every_model() {
+        for model in ExecutionModel::ALL {
+            let mut counter = EncryptedCounter::new(41, KEY).with_program(scaled_add(), &[3]);
+            let result = counter.increment(model, KEY);
+            let value = ContractHost::new(counter).reveal(KEY);
+            match model {
+                ExecutionModel::ClientSideZK => {
+                    assert_eq!(result.err(), Some(ExecutionError::ProofFailed), "{:?}", model);
+                    assert_eq!(value, Some(41), "{:?}", model);
+                }
+                _ => {
+                    assert!(result.is_ok(), "{:?}: {:?}", model, result.err());
+                    assert_eq!(value, Some(47), "{:?}: 41 + 2 · 3", model);
+                }
+            }
+        }
+    }
+    
+    #[test]
+    fn bytecode_round_trips_and_rejects_unknown_opcodes() {
+        let program = scaled_add();
+        assert_eq!(Program::decode(&program.encode()), Some(program));
+        assert!(Program::decode(&[0xff]).is_none());
+    }
+    
+    #[test]
+    fn same_program_same_steps_and_result() {
+        let run = || {
+            let mut slots = HashMap::from([(COUNTER_SLOT, 41u64)]);
+            scaled_add().run(&(), &mut slots, &[Operand::Public(3)]).map(|run| (run.steps, slots[&COUNTER_SLOT]))
+        };
+        assert_eq!(run(), Ok((7, 47)));
+        assert_eq!(run(), run());
+    }
+    
+    #[test]
+    fn runaway_loop_stopped_at_the_step_limit() {
+        let runaway = Program::new(vec![Instruction::Push(1), Instruction::Jnz(0)]);
+        assert_eq!(runaway.run::<u64>(&(), &mut HashMap::new(), &[]), Err(VmError::StepLimit(VM_MAX_STEPS)));
+    }
+    
+    // Only the plaintext executor can branch on the value or multiply freely
+    #[test]
+    fn executors_refuse_what_they_cannot_compute() {
+        let branch = Program::new(vec![
+            Instruction::Load(COUNTER_SLOT),
+            Instruction::Jnz(3),
+            Instruction::Halt,
+            Instruction::Push(5),
+            Instruction::Store(COUNTER_SLOT),
+        ]);
+        let square = Program::new(vec![
+            Instruction::Load(COUNTER_SLOT),
+            Instruction::Dup,
+            Instruction::Mul,
+            Instruction::Store(COUNTER_SLOT),
+        ]);
+        let mut plain_slots = HashMap::from([(COUNTER_SLOT, 41u64)]);
+        assert!(branch.run(&(), &mut plain_slots, &[]).is_ok());
+        assert_eq!(plain_slots[&COUNTER_SLOT], 5);
+        let mut lwe_slots = HashMap::from([(COUNTER_SLOT, LweSecretKey::derive(KEY).encrypt(41))]);
+        assert!(matches!(branch.run(&(), &mut lwe_slots, &[]), Err(VmError::Unsupported { .. })), "branch on a ciphertext");
+        let share_context = ShareContext { party: 0, parties: 5, alpha: Fp::ONE };
+        let mut share_slots = HashMap::from([(COUNTER_SLOT, AuthShare::zero(5))]);
+        assert!(matches!(square.run(&share_context, &mut share_slots, &[]), Err(VmError::Unsupported { .. })), "share × share without triples");
+    }
+}
@@ -0,0 +1,339 @@
+use wasmtime::{Caller, Config, Engine, Extern, Linker, Module, Store as WasmStore, Trap};
+use crate::keys::ViewingKey;
+use crate::storage::{EncryptedState, EncryptedStore};
+
+// WASM contract host (Wasmtime). A contract module exports `memory` and
+// `call(arg: i64)`, and reaches its state only through two host functions
+// in the "ecliptica" namespace, each over one slot's plaintext bytes:
+//   state_read(slot: i64, ptr: i32, len: i32) -> i32   slot length, -1 if empty
+//   state_write(slot: i64, ptr: i32, len: i32)
+// state_read copies the slot only if it fits in `len` bytes; a guest seeing
+// a length above `len` retries with a larger buffer.
+// The host decrypts and re-encrypts with the contract key; the guest only
+// ever sees the plaintext of its own slots. Execution is metered with fuel
+// (one unit per instruction), and host calls burn extra fuel for the
+// encryption they cost, per call and per byte moved.
+pub const WASM_DEFAULT_FUEL: u64 = 1_000_000;
+const WASM_READ_FUEL: u64 = 1_000;
+const WASM_READ_FUEL_PER_BYTE: u64 = 10;
+const WASM_WRITE_FUEL: u64 = 2_000;
+const WASM_WRITE_FUEL_PER_BYTE: u64 = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WasmError {
+    InvalidModule(String),
+    MissingExport(&'static str),
+    WrongSignature(&'static str),
+    OutOfFuel,
+    MemoryOutOfBounds,
+    Unreachable,
+    StackOverflow,
+    Arithmetic, // division by zero, integer overflow, bad float-to-int
+    StateDecrypt(u64),
+    Trap(String),
+}
+
+impl std::fmt::Display for WasmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WasmError::InvalidModule(reason) => write!(f, "module rejected: {}", reason),
+            WasmError::MissingExport(name) => write!(f, "module does not export `{}`", name),
+            WasmError::WrongSignature(name) => write!(f, "export `{}` has the wrong signature", name),
+            WasmError::OutOfFuel => write!(f, "out of fuel"),
+            WasmError::MemoryOutOfBounds => write!(f, "memory access out of bounds"),
+            WasmError::Unreachable => write!(f, "unreachable executed"),
+            WasmError::StackOverflow => write!(f, "call stack exhausted"),
+            WasmError::Arithmetic => write!(f, "arithmetic trap"),
+            WasmError::StateDecrypt(slot) => write!(f, "state in slot {} does not decrypt under the contract key", slot),
+            WasmError::Trap(reason) => write!(f, "trap: {}", reason),
+        }
+    }
+}
+
+impl WasmError {
+    fn from_trap(error: wasmtime::Error) -> Self {
+        match error.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => WasmError::OutOfFuel,
+            Some(Trap::MemoryOutOfBounds) => WasmError::MemoryOutOfBounds,
+            Some(Trap::UnreachableCodeReached) => WasmError::Unreachable,
+            Some(Trap::StackOverflow) => WasmError::StackOverflow,
+            Some(Trap::IntegerDivisionByZero | Trap::IntegerOverflow | Trap::BadConversionToInteger) => {
+                WasmError::Arithmetic
+            }
+            _ => WasmError::Trap(error.to_string()),
+        }
+    }
+}
+
+// Per-call host state: a working copy of the contract's slots and its
+// viewing key
+struct WasmContext {
+    slots: EncryptedStore,
+    key: ViewingKey,
+    // Set by a failing host function, which wasmtime reports as a plain error
+    error: Option<WasmError>,
+}
+
+pub struct WasmRun {
+    pub store: EncryptedStore,
+    pub fuel_used: u64,
+}
+
+pub struct WasmContract {
+    module: Module,
+}
+
+pub struct WasmHost {
+    engine: Engine,
+    linker: Linker<WasmContext>,
+}
+
+impl WasmHost {
+    pub fn new() -> Result<Self, WasmError> {
+        let mut config = Config::new();
+        config.consume_fuel(true);
+        config.cranelift_nan_canonicalization(true);
+        let engine = Engine::new(&config).map_err(|e| WasmError::Trap(e.to_string()))?;
+        
+        let mut linker = Linker::new(&engine);
+        linker
+            .func_wrap("ecliptica", "state_read", Self::state_read)
+            .and_then(|linker| linker.func_wrap("ecliptica", "state_write", Self::state_write))
+            .map_err(|e| WasmError::Trap(e.to_string()))?;
+        Ok(Self { engine, linker })
+    }
+    
+    // Binary or text format
+    pub fn load(&self, module: impl AsRef<[u8]>) -> Result<WasmContract, WasmError> {
+        let module = Module::new(&self.engine, module).map_err(|e| WasmError::InvalidModule(e.to_string()))?;
+        Ok(WasmContract { module })
+    }
+    
+    // Runs `call(arg)` against a fork of `store`, with the slots opened
+    // under `key` (the contract's viewing key); the updated store is
+    // returned only if the call completes
+    pub fn call(
+        &self,
+        contract: &WasmContract,
+        store: &EncryptedStore,
+        key: &ViewingKey,
+        arg: u64,
+        fuel: u64,
+    ) -> Result<WasmRun, WasmError> {
+        let context = WasmContext { slots: store.fork(), key: key.clone(), error: None };
+        let mut wasm_store = WasmStore::new(&self.engine, context);
+        wasm_store.set_fuel(fuel).map_err(|e| WasmError::Trap(e.to_string()))?;
+        let instance = self
+            .linker
+            .instantiate(&mut wasm_store, &contract.module)
+            .map_err(WasmError::from_trap)?;
+        let call = instance
+            .get_func(&mut wasm_store, "call")
+            .ok_or(WasmError::MissingExport("call"))?
+            .typed::<i64, ()>(&wasm_store)
+            .map_err(|_| WasmError::WrongSignature("call"))?;
+        
+        let outcome = call.call(&mut wasm_store, arg as i64);
+        let fuel_used = fuel - wasm_store.get_fuel().unwrap_or(0);
+        let context = wasm_store.into_data();
+        match (outcome, context.error) {
+            (Ok(()), _) => Ok(WasmRun { store: context.slots, fuel_used }),
+            (Err(_), Some(error)) => Err(error),
+            (Err(trap), None) => Err(WasmError::from_trap(trap)),
+        }
+    }
+    
+    fn host_error<T>(caller: &mut Caller<'_, WasmContext>, error: WasmError) -> wasmtime::Result<T> {
+        let message = error.to_string();
+        caller.data_mut().error = Some(error);
+        Err(wasmtime::Error::msg(message))
+    }
+    
+    fn charge(caller: &mut Caller<'_, WasmContext>, fuel: u64) -> wasmtime::Result<()> {
+        let left = caller.get_fuel()?;
+        if left < fuel {
+            caller.set_fuel(0)?;
+            return Self::host_error(caller, WasmError::OutOfFuel);
+        }
+        caller.set_fuel(left - fuel)
+    }
+    
+    fn memory(caller: &mut Caller<'_, WasmContext>) -> wasmtime::Result<wasmtime::Memory> {
+        match caller.get_export("memory") {
+            Some(Extern::Memory(memory)) => Ok(memory),
+            _ => Self::host_error(caller, WasmError::MissingExport("memory")),
+        }
+    }
+    
+    fn state_read(mut caller: Caller<'_, WasmContext>, slot: i64, ptr: i32, len: i32) -> wasmtime::Result<i32> {
+        Self::charge(&mut caller, WASM_READ_FUEL)?;
+        let memory = Self::memory(&mut caller)?;
+        let slot = slot as u64;
+        let context = caller.data_mut();
+        let (state, _) = context.slots.read(slot);
+        let Some(state) = state else {
+            return Ok(-1);
+        };
+        let Some(plaintext) = state.open(&context.key) else {
+            return Self::host_error(&mut caller, WasmError::StateDecrypt(slot));
+        };
+        // Decrypted either way, so charged either way
+        Self::charge(&mut caller, plaintext.len() as u64 * WASM_READ_FUEL_PER_BYTE)?;
+        let fits = plaintext.len() <= len.max(0) as usize;
+        if fits && memory.write(&mut caller, ptr as u32 as usize, &plaintext).is_err() {
+            return Self::host_error(&mut caller, WasmError::MemoryOutOfBounds);
+        }
+        Ok(plaintext.len() as i32)
+    }
+    
+    fn state_write(mut caller: Caller<'_, WasmContext>, slot: i64, ptr: i32, len: i32) -> wasmtime::Result<()> {
+        let len = len.max(0) as usize;
+        Self::charge(&mut caller, WASM_WRITE_FUEL + len as u64 * WASM_WRITE_FUEL_PER_BYTE)?;
+        let memory = Self::memory(&mut caller)?;
+        // Bounds-checked against the guest's memory before anything is
+        // allocated for it
+        let bytes = memory.data(&caller).get(ptr as u32 as usize..).and_then(|tail| tail.get(..len));
+        let Some(plaintext) = bytes.map(<[u8]>::to_vec) else {
+            return Self::host_error(&mut caller, WasmError::MemoryOutOfBounds);
+        };
+        // Written back where the slot's state sits, in its padding bucket,
+        // or for a new slot at the store's key epoch. `key` is the contract
+        // key, or the epoch key once the store has been rotated.
+        let (slot, context) = (slot as u64, caller.data_mut());
+        let state = match context.slots.get(slot) {
+            Some(previous) => previous.reseal(&plaintext, &context.key, &previous.same_bucket()),
+            None => {
+                let (path, epoch) = (context.key.path(), context.slots.key_epoch());
+                let path = path.with_epoch(epoch).unwrap_or_else(|| path.clone().epoch(epoch));
+                EncryptedState::at(path.slot(slot), &plaintext, &context.key)
+            }
+        };
+        let Some(state) = state else {
+            return Self::host_error(&mut caller, WasmError::StateDecrypt(slot));
+        };
+        context.slots.write(slot, state);
+        Ok(())
+    }
+}
+
+// Counter contract as a WASM module: slot 0 (u64 LE) += arg
+pub const COUNTER_WASM: &str = r#"
+(module
+  (import "ecliptica" "state_read" (func $read (param i64 i32 i32) (result i32)))
+  (import "ecliptica" "state_write" (func $write (param i64 i32 i32)))
+  (memory (export "memory") 1)
+  (func (export "call") (param $amount i64)
+    (drop (call $read (i64.const 0) (i32.const 0) (i32.const 8)))
+    (i64.store (i32.const 0) (i64.add (i64.load (i32.const 0)) (local.get $amount)))
+    (call $write (i64.const 0) (i32.const 0) (i32.const 8))))
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{COUNTER_CONTRACT_ID, COUNTER_SLOT, EncryptedCounter};
+    use crate::keys::KeyPath;
+    use crate::storage::PaddingPolicy;
+    use crate::tests::{KEY, wasm_guest};
+    
+    fn wasm_store(padding: &PaddingPolicy) -> EncryptedStore {
+        let mut store = EncryptedStore::new();
+        store.write(COUNTER_SLOT, EncryptedCounter::new(0, KEY).seal(KEY, &41u64.to_le_bytes(), padding));
+        store
+    }
+    
+    fn wasm_slot_value(store: &EncryptedStore) -> Option<u64> {
+        let bytes = store.get(COUNTER_SLOT)?.open(&EncryptedCounter::viewing_key(KEY))?;
+        Some(u64::from_le_bytes(bytes.try_into().ok()?))
+    }
+    
+    // Counter as a WASM module, state through host functions, fuel-metered
+    #[test]
+    fn wasm_counter_runs_on_encrypted_state() {
+        let host = WasmHost::new().expect("Wasmtime engine");
+        let counter = host.load(COUNTER_WASM).expect("counter module");
+        let store = wasm_store(&PaddingPolicy::None);
+        let counter_key = EncryptedCounter::viewing_key(KEY);
+        let run = host.call(&counter, &store, &counter_key, 2, WASM_DEFAULT_FUEL).expect("call");
+        assert_eq!(wasm_slot_value(&run.store), Some(43));
+        assert_eq!(run.store.touched(), 1, "call touched only the counter slot");
+        assert_eq!(wasm_slot_value(&store), Some(41), "caller's store unchanged until commit");
+        assert!(run.fuel_used > WASM_READ_FUEL + WASM_WRITE_FUEL, "host calls charged above instruction fuel");
+        let rerun = host.call(&counter, &store, &counter_key, 2, WASM_DEFAULT_FUEL).expect("call");
+        assert_eq!(rerun.fuel_used, run.fuel_used, "same call, same fuel");
+        let starved = host.call(&counter, &store, &counter_key, 2, run.fuel_used - 1);
+        assert_eq!(starved.err(), Some(WasmError::OutOfFuel));
+    }
+    
+    // A buffer too short for the slot gets nothing copied and the length it
+    // needs; reads are charged per byte decrypted
+    #[test]
+    fn wasm_read_reports_the_length_it_needs() {
+        let host = WasmHost::new().expect("Wasmtime engine");
+        let counter_key = EncryptedCounter::viewing_key(KEY);
+        let short_read = host
+            .load(wasm_guest(
+                "(i64.store (i32.const 16) (i64.extend_i32_s (call $read (i64.const 0) (i32.const 0) (i32.const 4))))
+                 (call $write (i64.const 1) (i32.const 16) (i32.const 8))
+                 (call $write (i64.const 2) (i32.const 0) (i32.const 4))",
+            ))
+            .expect("module loads");
+        let run = host.call(&short_read, &wasm_store(&PaddingPolicy::None), &counter_key, 0, WASM_DEFAULT_FUEL).expect("call");
+        let open = |slot| run.store.get(slot).and_then(|state| state.open(&counter_key));
+        assert_eq!(open(1), Some(8u64.to_le_bytes().to_vec()), "length of the 8-byte slot");
+        assert_eq!(open(2), Some(vec![0; 4]), "nothing copied into the short buffer");
+        
+        let mut long = EncryptedStore::new();
+        let path = KeyPath::contract(COUNTER_CONTRACT_ID).epoch(0).slot(COUNTER_SLOT);
+        long.write(COUNTER_SLOT, EncryptedState::at(path, &[7; 64], &counter_key).expect("sealed"));
+        let long_run = host.call(&short_read, &long, &counter_key, 0, WASM_DEFAULT_FUEL).expect("call");
+        assert_eq!(long_run.fuel_used - run.fuel_used, (64 - 8) * WASM_READ_FUEL_PER_BYTE);
+    }
+    
+    #[test]
+    fn wasm_written_slot_keeps_its_padding_bucket() {
+        let host = WasmHost::new().expect("Wasmtime engine");
+        let counter = host.load(COUNTER_WASM).expect("counter module");
+        let store = wasm_store(&PaddingPolicy::fixed());
+        let run = host.call(&counter, &store, &EncryptedCounter::viewing_key(KEY), 2, WASM_DEFAULT_FUEL).expect("call");
+        assert_eq!(run.store.get(COUNTER_SLOT).map(EncryptedState::padded_len), Some(256));
+    }
+    
+    // Every way a guest can go wrong ends in its own typed error
+    #[test]
+    fn wasm_guest_faults_are_typed() {
+        let host = WasmHost::new().expect("Wasmtime engine");
+        let store = wasm_store(&PaddingPolicy::None);
+        let counter_key = EncryptedCounter::viewing_key(KEY);
+        let call = |module: &str| {
+            let contract = host.load(module).expect("module loads");
+            host.call(&contract, &store, &counter_key, 2, WASM_DEFAULT_FUEL).err()
+        };
+        for (case, module, expected) in [
+            ("infinite loop", wasm_guest("(loop $spin (br $spin))"), WasmError::OutOfFuel),
+            ("load past linear memory", wasm_guest("(drop (i64.load (i32.const 70000)))"), WasmError::MemoryOutOfBounds),
+            ("host call with a bad pointer", wasm_guest("(call $write (i64.const 0) (i32.const 65535) (i32.const 8))"), WasmError::MemoryOutOfBounds),
+            ("write past linear memory", wasm_guest("(call $write (i64.const 0) (i32.const 0) (i32.const 70000))"), WasmError::MemoryOutOfBounds),
+            // Charged before anything is read
+            ("2GB write", wasm_guest("(call $write (i64.const 0) (i32.const 0) (i32.const 0x7fffffff))"), WasmError::OutOfFuel),
+            ("unreachable", wasm_guest("unreachable"), WasmError::Unreachable),
+            (
+                "`call` with the wrong signature",
+                r#"(module (memory (export "memory") 1) (func (export "call") (param i32)))"#.to_string(),
+                WasmError::WrongSignature("call"),
+            ),
+        ] {
+            assert_eq!(call(&module), Some(expected), "{}", case);
+        }
+    }
+    
+    #[test]
+    fn wasm_rejects_wrong_keys_and_malformed_modules() {
+        let host = WasmHost::new().expect("Wasmtime engine");
+        let counter = host.load(COUNTER_WASM).expect("counter module");
+        let wrong_key = EncryptedCounter::viewing_key(b"wrong key");
+        let run = host.call(&counter, &wasm_store(&PaddingPolicy::None), &wrong_key, 2, WASM_DEFAULT_FUEL);
+        assert_eq!(run.err(), Some(WasmError::StateDecrypt(COUNTER_SLOT)));
+        assert!(matches!(host.load(b"\0asm"), Err(WasmError::InvalidModule(_))));
+    }
+}
@@ -0,0 +1,431 @@
+use rand::Rng;
+use sha3::{Shake256, digest::{Update, ExtendableOutput, XofReader}};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+use winterfell::crypto::{hashers::Blake3_256, DefaultRandomCoin};
+use winterfell::math::{fields::f128::BaseElement, FieldElement, ToElements};
+use winterfell::matrix::ColMatrix;
+use winterfell::{
+    AcceptableOptions, Air, AirContext, Assertion, AuxRandElements, ConstraintCompositionCoefficients,
+    DefaultConstraintEvaluator, DefaultTraceLde, EvaluationFrame, FieldExtension, Proof, ProofOptions,
+    Prover, StarkDomain, TraceInfo, TracePolyTable, TraceTable, TransitionConstraintDegree,
+};
+
+// Counter transition STARK (Winterfell). A value commitment is a MiMC-style
+// Feistel chain over f128 started from (value, blinding); the AIR runs the
+// old and new chains side by side and ties their first rows together with
+// new = old + 1. Each chain absorbs the commitment to its ciphertext after
+// the first round, so a value commitment belongs to one state and the proof
+// cannot be reattached to another; with the blinding derived from that
+// ciphertext (EncryptedState::value_blinding), a key holder who opens the
+// state can check its plaintext against the commitment.
+//
+// Masking: the chains fill the first 64 rows and the remaining 192 rows are
+// uniformly random, with the transition constraints switched off there.
+// Each column is then a degree-255 polynomial with 192 random degrees of
+// freedom, more than the 66 evaluations per column a proof discloses (32
+// query openings, the same points shifted by one row through the
+// composition polynomial, and the out-of-domain frame), so the openings are
+// independent of value and blinding. The composition polynomial and Merkle
+// leaves are not salted: PoC masking, not an audited zero-knowledge proof.
+pub const COMMITMENT_TRACE_LENGTH: usize = 64;
+pub(crate) const COUNTER_TRACE_LENGTH: usize = 4 * COMMITMENT_TRACE_LENGTH;
+const COUNTER_TRACE_WIDTH: usize = 4; // old (a, b), new (a, b)
+
+fn commitment_round_constants() -> Vec<BaseElement> {
+    let mut reader = Shake256::default()
+        .chain(b"ecliptica/counter-commitment")
+        .finalize_xof();
+    (0..COMMITMENT_TRACE_LENGTH)
+        .map(|_| {
+            let mut bytes = [0u8; 16];
+            reader.read(&mut bytes);
+            BaseElement::new(u128::from_le_bytes(bytes))
+        })
+        .collect()
+}
+
+// One Feistel round: (a, b) -> ((a + b)^3 + k, a). Cubing is a permutation
+// of f128 since 3 does not divide p - 1.
+fn commitment_round<E: FieldElement>(a: E, b: E, k: E) -> (E, E) {
+    let x = a + b;
+    (x * x * x + k, a)
+}
+
+// Limbs of a state commitment as they enter the chain
+fn state_limbs(state: &[u8; 32]) -> (BaseElement, BaseElement) {
+    let (low, high) = state.split_at(16);
+    (
+        BaseElement::new(u128::from_le_bytes(low.try_into().expect("16 bytes"))),
+        BaseElement::new(u128::from_le_bytes(high.try_into().expect("16 bytes"))),
+    )
+}
+
+// Hiding, binding commitment to a counter value held in `state`
+pub fn value_commitment(value: u64, blinding: BaseElement, state: &[u8; 32]) -> BaseElement {
+    let (low, high) = state_limbs(state);
+    let (mut a, mut b) = (BaseElement::from(value), blinding);
+    for (step, k) in commitment_round_constants().into_iter().take(COMMITMENT_TRACE_LENGTH - 1).enumerate() {
+        (a, b) = commitment_round(a, b, k);
+        if step == 0 {
+            (a, b) = (a + low, b + high);
+        }
+    }
+    a
+}
+
+pub fn random_blinding() -> BaseElement {
+    BaseElement::new(rand::thread_rng().gen::<u128>())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CounterTransition {
+    pub old_commitment: BaseElement,
+    pub new_commitment: BaseElement,
+    // Ciphertext commitments the two chains absorb
+    pub old_state: [u8; 32],
+    pub new_state: [u8; 32],
+}
+
+impl ToElements<BaseElement> for CounterTransition {
+    fn to_elements(&self) -> Vec<BaseElement> {
+        let (old_low, old_high) = state_limbs(&self.old_state);
+        let (new_low, new_high) = state_limbs(&self.new_state);
+        vec![self.old_commitment, self.new_commitment, old_low, old_high, new_low, new_high]
+    }
+}
+
+pub struct CounterTransitionAir {
+    context: AirContext<BaseElement>,
+    transition: CounterTransition,
+}
+
+impl Air for CounterTransitionAir {
+    type BaseField = BaseElement;
+    type PublicInputs = CounterTransition;
+    type GkrProof = ();
+    type GkrVerifier = ();
+
+    fn new(trace_info: TraceInfo, pub_inputs: CounterTransition, options: ProofOptions) -> Self {
+        assert_eq!(COUNTER_TRACE_WIDTH, trace_info.width());
+        let degrees = vec![
+            TransitionConstraintDegree::with_cycles(3, vec![COUNTER_TRACE_LENGTH]),
+            TransitionConstraintDegree::with_cycles(1, vec![COUNTER_TRACE_LENGTH]),
+            TransitionConstraintDegree::with_cycles(3, vec![COUNTER_TRACE_LENGTH]),
+            TransitionConstraintDegree::with_cycles(1, vec![COUNTER_TRACE_LENGTH]),
+            TransitionConstraintDegree::with_cycles(1, vec![COUNTER_TRACE_LENGTH]),
+        ];
+        Self {
+            context: AirContext::new(trace_info, degrees, 2, options),
+            transition: pub_inputs,
+        }
+    }
+
+    fn context(&self) -> &AirContext<Self::BaseField> {
+        &self.context
+    }
+
+    fn evaluate_transition<E: FieldElement + From<Self::BaseField>>(
+        &self,
+        frame: &EvaluationFrame<E>,
+        periodic_values: &[E],
+        result: &mut [E],
+    ) {
+        let (current, next) = (frame.current(), frame.next());
+        let (k, is_first, in_chain) = (periodic_values[0], periodic_values[1], periodic_values[2]);
+
+        // Rounds hold along the chains only; the masking rows are free.
+        // After the first round each chain absorbs its state commitment.
+        let (old_low, old_high) = state_limbs(&self.transition.old_state);
+        let (new_low, new_high) = state_limbs(&self.transition.new_state);
+        let (old_a, old_b) = commitment_round(current[0], current[1], k);
+        let (new_a, new_b) = commitment_round(current[2], current[3], k);
+        result[0] = in_chain * (next[0] - old_a - is_first * E::from(old_low));
+        result[1] = in_chain * (next[1] - old_b - is_first * E::from(old_high));
+        result[2] = in_chain * (next[2] - new_a - is_first * E::from(new_low));
+        result[3] = in_chain * (next[3] - new_b - is_first * E::from(new_high));
+        // Both chains start from the committed values: new = old + 1
+        result[4] = is_first * (current[2] - current[0] - E::ONE);
+    }
+
+    fn get_assertions(&self) -> Vec<Assertion<Self::BaseField>> {
+        let last_step = COMMITMENT_TRACE_LENGTH - 1;
+        vec![
+            Assertion::single(0, last_step, self.transition.old_commitment),
+            Assertion::single(2, last_step, self.transition.new_commitment),
+        ]
+    }
+
+    fn get_periodic_column_values(&self) -> Vec<Vec<Self::BaseField>> {
+        let mut constants = commitment_round_constants();
+        constants.resize(COUNTER_TRACE_LENGTH, BaseElement::ZERO);
+        let mut is_first = vec![BaseElement::ZERO; COUNTER_TRACE_LENGTH];
+        is_first[0] = BaseElement::ONE;
+        let mut in_chain = vec![BaseElement::ZERO; COUNTER_TRACE_LENGTH];
+        in_chain[..COMMITMENT_TRACE_LENGTH - 1].fill(BaseElement::ONE);
+        vec![constants, is_first, in_chain]
+    }
+}
+
+fn counter_proof_options() -> ProofOptions {
+    ProofOptions::new(32, 8, 0, FieldExtension::None, 4, 31)
+}
+
+struct CounterTransitionProver {
+    options: ProofOptions,
+    old_state: [u8; 32],
+    new_state: [u8; 32],
+}
+
+impl Prover for CounterTransitionProver {
+    type BaseField = BaseElement;
+    type Air = CounterTransitionAir;
+    type Trace = TraceTable<BaseElement>;
+    type HashFn = Blake3_256<BaseElement>;
+    type RandomCoin = DefaultRandomCoin<Self::HashFn>;
+    type TraceLde<E: FieldElement<BaseField = Self::BaseField>> = DefaultTraceLde<E, Self::HashFn>;
+    type ConstraintEvaluator<'a, E: FieldElement<BaseField = Self::BaseField>> =
+        DefaultConstraintEvaluator<'a, Self::Air, E>;
+
+    fn get_pub_inputs(&self, trace: &Self::Trace) -> CounterTransition {
+        let last_step = COMMITMENT_TRACE_LENGTH - 1;
+        CounterTransition {
+            old_commitment: trace.get(0, last_step),
+            new_commitment: trace.get(2, last_step),
+            old_state: self.old_state,
+            new_state: self.new_state,
+        }
+    }
+
+    fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+
+    fn new_trace_lde<E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+        trace_info: &TraceInfo,
+        main_trace: &ColMatrix<Self::BaseField>,
+        domain: &StarkDomain<Self::BaseField>,
+    ) -> (Self::TraceLde<E>, TracePolyTable<E>) {
+        DefaultTraceLde::new(trace_info, main_trace, domain)
+    }
+
+    fn new_evaluator<'a, E: FieldElement<BaseField = Self::BaseField>>(
+        &self,
+        air: &'a Self::Air,
+        aux_rand_elements: Option<AuxRandElements<E>>,
+        composition_coefficients: ConstraintCompositionCoefficients<E>,
+    ) -> Self::ConstraintEvaluator<'a, E> {
+        DefaultConstraintEvaluator::new(air, aux_rand_elements, composition_coefficients)
+    }
+}
+
+// Client side: prove that the commitment to `old_value` in `old_state` and
+// the commitment to old_value + 1 in `new_state` are consistent. Returns the
+// public inputs and the serialized proof.
+pub fn prove_counter_transition(
+    old_value: u64,
+    old_blinding: BaseElement,
+    old_state: [u8; 32],
+    new_blinding: BaseElement,
+    new_state: [u8; 32],
+) -> Option<(CounterTransition, Vec<u8>)> {
+    let new_value = old_value.checked_add(1)?;
+    let constants = commitment_round_constants();
+    let ((old_low, old_high), (new_low, new_high)) = (state_limbs(&old_state), state_limbs(&new_state));
+    let mut trace = TraceTable::new(COUNTER_TRACE_WIDTH, COUNTER_TRACE_LENGTH);
+    trace.fill(
+        |state| {
+            state[0] = BaseElement::from(old_value);
+            state[1] = old_blinding;
+            state[2] = BaseElement::from(new_value);
+            state[3] = new_blinding;
+        },
+        |step, state| {
+            if step + 1 < COMMITMENT_TRACE_LENGTH {
+                let k = constants[step];
+                (state[0], state[1]) = commitment_round(state[0], state[1], k);
+                (state[2], state[3]) = commitment_round(state[2], state[3], k);
+                if step == 0 {
+                    (state[0], state[1]) = (state[0] + old_low, state[1] + old_high);
+                    (state[2], state[3]) = (state[2] + new_low, state[3] + new_high);
+                }
+            } else {
+                state.iter_mut().for_each(|cell| *cell = random_blinding());
+            }
+        },
+    );
+
+    let prover = CounterTransitionProver { options: counter_proof_options(), old_state, new_state };
+    let transition = prover.get_pub_inputs(&trace);
+    let proof = prover.prove(trace).ok()?;
+    Some((transition, proof.to_bytes()))
+}
+
+// Runs `prove` on its own thread after sleeping out `stall`. A caller that
+// stops waiting sets `abandoned`; a prover that sees it once its stall is
+// over exits without proving, and a late proof goes nowhere.
+pub(crate) fn spawn_prover<T: Send + 'static>(
+    stall: Option<Duration>,
+    abandoned: Arc<AtomicBool>,
+    prove: impl FnOnce() -> T + Send + 'static,
+) -> Receiver<T> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        if let Some(stall) = stall {
+            thread::sleep(stall);
+        }
+        if !abandoned.load(Ordering::Relaxed) {
+            let _ = sender.send(prove());
+        }
+    });
+    receiver
+}
+
+// Verifier side: needs only the public inputs and the proof bytes
+pub fn verify_counter_transition(transition: CounterTransition, proof: &[u8]) -> bool {
+    let Ok(proof) = Proof::from_bytes(proof) else {
+        return false;
+    };
+    let acceptable = AcceptableOptions::OptionSet(vec![counter_proof_options()]);
+    winterfell::verify::<
+        CounterTransitionAir,
+        Blake3_256<BaseElement>,
+        DefaultRandomCoin<Blake3_256<BaseElement>>,
+    >(proof, transition, &acceptable)
+    .is_ok()
+}
+
+// How long a hybrid transition stays open to challenge. Simulated, like
+// OptimisticConfig's window: nothing in the benchmark waits it out.
+pub const HYBRID_CHALLENGE_WINDOW: Duration = Duration::from_secs(600);
+
+// What the hybrid model reports next to timings
+#[derive(Debug, Clone, Copy)]
+pub struct HybridLatency {
+    pub optimistic_us: u64,     // until the enclave's signed transition is accepted
+    pub proof_us: Option<u64>,  // enclave proving after that; None past the deadline
+}
+
+impl HybridLatency {
+    // Worst case for a challenged transition: challenged as the window
+    // closes, settled once the proof exists and the challenger has checked
+    // it. A transition with no proof is reverted instead.
+    pub fn challenge_us(&self, window: Duration, verify_us: u64) -> Option<u64> {
+        Some(window.as_micros() as u64 + self.proof_us? + verify_us)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        DEFAULT_PROVER_DEADLINE, EncryptedCounter, ExecutionError, ExecutionEvidence, ExecutionModel,
+        InjectedFault, Rejection,
+    };
+    use crate::keys::ViewingKey;
+    use crate::storage::{EncryptedState, PaddingPolicy};
+    use crate::tests::{KEY, fault_report};
+    
+    // The verifier sees only the on-chain commitment, the new one and the proof
+    #[test]
+    fn zk_proof_binds_old_and_new_commitments() {
+        let mut counter = EncryptedCounter::new(41, KEY);
+        let on_chain = counter.value_commitment();
+        counter.increment(ExecutionModel::ClientSideZK, KEY).expect("ZK increment");
+        let Some(ExecutionEvidence::ZkProof { transition, proof }) = counter.evidence().cloned() else {
+            panic!("ClientSideZK produced no STARK");
+        };
+        let forged_new = CounterTransition {
+            new_commitment: value_commitment(43, random_blinding(), &transition.new_state),
+            ..transition
+        };
+        let forged_old = CounterTransition {
+            old_commitment: value_commitment(41, random_blinding(), &transition.old_state),
+            ..transition
+        };
+        assert_eq!(transition.old_commitment, on_chain);
+        assert!(verify_counter_transition(transition, &proof), "valid transition accepted");
+        assert!(!verify_counter_transition(forged_new, &proof), "commitment to old + 2 rejected");
+        assert!(!verify_counter_transition(forged_old, &proof), "foreign old commitment rejected");
+    }
+    
+    #[test]
+    fn zk_prover_refuses_a_skipped_step() {
+        let snapshot = EncryptedCounter::new(41, KEY).snapshot();
+        assert!(EncryptedCounter::generate_zk_proof(&snapshot, snapshot.state(), &EncryptedCounter::viewing_key(KEY), (41, 43), DEFAULT_PROVER_DEADLINE, None).is_err());
+    }
+    
+    // Given up at the deadline, not after waiting the stall out
+    #[test]
+    fn stalled_prover_abandoned_at_the_deadline() {
+        let deadline = DEFAULT_PROVER_DEADLINE.as_micros() as u64;
+        let abandoned = deadline..2 * deadline;
+        for model in ExecutionModel::ALL.into_iter().filter(|&model| InjectedFault::ProverTimeout.applies_to(model)) {
+            let (_, us) = fault_report(model).failure_path(InjectedFault::ProverTimeout).expect("stall injected");
+            assert!(abandoned.contains(&us), "{:?}: gave up after {}μs", model, us);
+        }
+    }
+    
+    // A prover still stalled when its caller gives up never starts proving
+    #[test]
+    fn abandoned_prover_exits_without_proving() {
+        let stall = Some(Duration::from_millis(10));
+        let abandoned = spawn_prover(stall, Arc::new(AtomicBool::new(true)), || 7);
+        assert!(abandoned.recv().is_err(), "thread ended with nothing sent");
+        assert_eq!(spawn_prover(stall, Arc::new(AtomicBool::new(false)), || 7).recv(), Ok(7));
+    }
+    
+    // A deadline from the contract's configuration, not the build profile
+    #[test]
+    fn prover_deadline_is_configurable() {
+        let counter = EncryptedCounter::new(41, KEY).with_prover_deadline(Duration::from_millis(20));
+        let stall = Some(InjectedFault::ProverTimeout);
+        let (result, _) = counter.execute_with_fault(ExecutionModel::ClientSideZK, &counter.snapshot(), KEY, stall);
+        assert_eq!(result.error, Some(ExecutionError::ProverTimeout { deadline_ms: 20 }));
+        assert!(result.total_time_us < DEFAULT_PROVER_DEADLINE.as_micros() as u64);
+    }
+    
+    // The AIR binds each commitment to its ciphertext: an honest 41 -> 42
+    // proof fails once paired with a state that seals 100, or with the
+    // right state against a stale previous one
+    #[test]
+    fn proof_is_bound_to_its_ciphertexts() {
+        let mut counter = EncryptedCounter::new(41, KEY);
+        let validator = counter.validator();
+        let previous = counter.state_commitment();
+        counter.increment(ExecutionModel::ClientSideZK, KEY).expect("ZK increment");
+        let Some(ExecutionEvidence::ZkProof { transition, proof }) = counter.evidence().cloned() else {
+            panic!("ClientSideZK produced no STARK");
+        };
+        let sealed_100 = counter.seal(KEY, &100u64.to_le_bytes(), &PaddingPolicy::None);
+        let moved = CounterTransition { new_state: sealed_100.commitment, ..transition };
+        let replayed = CounterTransition { old_state: transition.new_state, ..transition };
+        assert!(verify_counter_transition(transition, &proof));
+        assert!(!verify_counter_transition(moved, &proof), "proof moved to another ciphertext");
+        assert!(!verify_counter_transition(replayed, &proof), "proof moved to another previous state");
+        let moved = ExecutionEvidence::ZkProof { transition: moved, proof };
+        assert_eq!(validator.validate(&previous, &sealed_100, &moved), Err(Rejection::InvalidProof));
+    }
+    
+    // A key holder opening a state checks it against the commitment: one
+    // made out for 42 does not match a state that seals 100
+    #[test]
+    fn commitment_checks_against_the_plaintext() {
+        let (counter, viewing_key) = (EncryptedCounter::new(41, KEY), EncryptedCounter::viewing_key(KEY));
+        let sealed_42 = counter.seal(KEY, &42u64.to_le_bytes(), &PaddingPolicy::None);
+        let sealed_100 = counter.seal(KEY, &100u64.to_le_bytes(), &PaddingPolicy::None);
+        let commit = |value, state: &EncryptedState| {
+            value_commitment(value, state.value_blinding(&viewing_key).expect("own key"), &state.commitment)
+        };
+        let slot_key = viewing_key.derive(&sealed_42.key_path).expect("own subtree");
+        assert_eq!(sealed_42.value_blinding(&slot_key), sealed_42.value_blinding(&viewing_key), "every key over the slot agrees");
+        assert_ne!(commit(42, &sealed_42), commit(42, &sealed_100), "bound to the ciphertext");
+        assert_ne!(commit(42, &sealed_42), commit(100, &sealed_42), "bound to the value");
+        let stranger = ViewingKey::master(b"another owner");
+        assert_ne!(sealed_42.value_blinding(&stranger), sealed_42.value_blinding(&viewing_key), "hidden from other keys");
+    }
+}